@@ -1,19 +1,46 @@
 // use sled;
-use crate::{error::Error, event::verifiable_event::VerifiableEvent};
-use keri::prefix::IdentifierPrefix;
+use crate::{
+    error::Error,
+    event::{verifiable_event::VerifiableEvent, Event},
+    state::ManagerTelState,
+};
+use keri::{
+    derivation::self_addressing::SelfAddressing,
+    prefix::{IdentifierPrefix, Prefix, SelfAddressingPrefix},
+};
+use serde::{Deserialize, Serialize};
 use sled_tables::{
     self,
     tables::{SledEventTree, SledEventTreeVec},
 };
-use std::path::Path;
+use std::{io::ErrorKind, path::Path};
 
 pub struct EventDatabase {
+    db: sled::Db,
     // "iids" tree
     identifiers: SledEventTree<IdentifierPrefix>,
     // "tels" tree
     tel_events: SledEventTreeVec<VerifiableEvent>,
     // "man" tree
     management_events: SledEventTreeVec<VerifiableEvent>,
+    // "mesc" tree
+    escrowed_management_events: SledEventTreeVec<VerifiableEvent>,
+    // "unkn" tree, events of types unknown to this version of the library
+    unknown_events: SledEventTreeVec<VerifiableEvent>,
+    // raw handles of "tels", "mans", "mesc" and "unkn" trees, for debugging
+    // and clearing purposes
+    raw_tel_events: sled::Tree,
+    raw_management_events: sled::Tree,
+    raw_escrowed_management_events: sled::Tree,
+    raw_unknown_events: sled::Tree,
+    // "mchk" tree, management states stored as checkpoints
+    management_checkpoints: sled::Tree,
+    // "ttip" and "mtip" trees, tips of vc and management tels
+    tel_tips: sled::Tree,
+    management_tips: sled::Tree,
+    // "rvcs" tree, vcs issued in registry, keyed by registry key followed
+    // by vc key
+    registry_vcs: sled::Tree,
 }
 
 impl EventDatabase {
@@ -21,12 +48,60 @@ impl EventDatabase {
     where
         P: Into<&'a Path>,
     {
-        let db = sled::open(path.into())?;
-        Ok(Self {
+        let path = path.into();
+        let db = sled::open(path).map_err(|e| open_error(path, e))?;
+        let tels = db.open_tree(b"tels")?;
+        let mans = db.open_tree(b"mans")?;
+        let mesc = db.open_tree(b"mesc")?;
+        let unkn = db.open_tree(b"unkn")?;
+        let db = Self {
             identifiers: SledEventTree::new(db.open_tree(b"iids")?),
-            tel_events: SledEventTreeVec::new(db.open_tree(b"tels")?),
-            management_events: SledEventTreeVec::new(db.open_tree(b"mans")?),
-        })
+            tel_events: SledEventTreeVec::new(tels.clone()),
+            management_events: SledEventTreeVec::new(mans.clone()),
+            escrowed_management_events: SledEventTreeVec::new(mesc.clone()),
+            unknown_events: SledEventTreeVec::new(unkn.clone()),
+            raw_tel_events: tels,
+            raw_management_events: mans,
+            raw_escrowed_management_events: mesc,
+            raw_unknown_events: unkn,
+            management_checkpoints: db.open_tree(b"mchk")?,
+            tel_tips: db.open_tree(b"ttip")?,
+            management_tips: db.open_tree(b"mtip")?,
+            registry_vcs: db.open_tree(b"rvcs")?,
+            db,
+        };
+        // Index vcs stored before the index was kept.
+        if db.registry_vcs.is_empty() && !db.raw_tel_events.is_empty() {
+            for id in db.get_identifiers() {
+                if let Some(issuance) = db.get_events(&id).and_then(|mut events| events.next()) {
+                    db.index_vc(&issuance, &id)?;
+                }
+            }
+        }
+        Ok(db)
+    }
+
+    /// Writes all buffered changes to disk.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Removes all stored events, escrowed events and checkpoints.
+    pub fn clear(&self) -> Result<(), Error> {
+        self.raw_tel_events.clear()?;
+        self.raw_management_events.clear()?;
+        self.raw_escrowed_management_events.clear()?;
+        self.raw_unknown_events.clear()?;
+        self.management_checkpoints.clear()?;
+        self.tel_tips.clear()?;
+        self.management_tips.clear()?;
+        self.registry_vcs.clear()?;
+        Ok(())
+    }
+
+    pub fn get_identifiers(&self) -> impl DoubleEndedIterator<Item = IdentifierPrefix> {
+        self.identifiers.iter()
     }
 
     pub fn add_new_event(
@@ -34,9 +109,14 @@ impl EventDatabase {
         event: VerifiableEvent,
         id: &IdentifierPrefix,
     ) -> Result<(), Error> {
-        Ok(self
-            .tel_events
-            .push(self.identifiers.designated_key(id), event.into())?)
+        let tip = self.get_tip(id)?;
+        let new_tip = TelTip::append(tip.as_ref(), &event)?;
+        self.tel_events
+            .push(self.identifiers.designated_key(id), event.clone().into())?;
+        if tip.is_none() {
+            self.index_vc(&event, id)?;
+        }
+        self.set_tip(&self.tel_tips, id, Some(new_tip))
     }
 
     pub fn get_events(
@@ -47,14 +127,147 @@ impl EventDatabase {
             .iter_values(self.identifiers.designated_key(id))
     }
 
+    /// Replaces all stored events of given identifier.
+    pub fn set_events(
+        &self,
+        events: Vec<VerifiableEvent>,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        let previous = self.get_events(id).and_then(|mut events| events.next());
+        let issuance = events.first().cloned();
+        let tip = TelTip::of(&events)?;
+        self.tel_events
+            .put(self.identifiers.designated_key(id), events)?;
+        if let Some(previous) = previous {
+            self.unindex_vc(&previous, id)?;
+        }
+        if let Some(issuance) = issuance {
+            self.index_vc(&issuance, id)?;
+        }
+        self.set_tip(&self.tel_tips, id, tip)
+    }
+
+    /// Returns identifiers of vcs which issuance is stored in given
+    /// registry, in order they were first stored.
+    pub fn get_registry_vcs(
+        &self,
+        registry_id: &IdentifierPrefix,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        let key = self.identifiers.designated_key(registry_id).to_be_bytes();
+        self.registry_vcs
+            .scan_prefix(key)
+            .values()
+            .map(|value| -> Result<IdentifierPrefix, Error> {
+                Ok(String::from_utf8_lossy(&value?).parse()?)
+            })
+            .collect()
+    }
+
+    // Adds vc to index of registry its first event is issued in.
+    fn index_vc(&self, issuance: &VerifiableEvent, id: &IdentifierPrefix) -> Result<(), Error> {
+        if let Some(key) = self.registry_vc_key(issuance, id) {
+            self.registry_vcs.insert(key, id.to_str().as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn unindex_vc(&self, issuance: &VerifiableEvent, id: &IdentifierPrefix) -> Result<(), Error> {
+        if let Some(key) = self.registry_vc_key(issuance, id) {
+            self.registry_vcs.remove(key)?;
+        }
+        Ok(())
+    }
+
+    fn registry_vc_key(
+        &self,
+        issuance: &VerifiableEvent,
+        id: &IdentifierPrefix,
+    ) -> Option<Vec<u8>> {
+        let registry_id = match issuance.event {
+            Event::Vc(ref vc) => vc.event.registry_id()?,
+            _ => return None,
+        };
+        let mut key = self
+            .identifiers
+            .designated_key(&registry_id)
+            .to_be_bytes()
+            .to_vec();
+        key.extend(&self.identifiers.designated_key(id).to_be_bytes());
+        Some(key)
+    }
+
+    /// Returns tip of vc tel of given identifier, or `None` if it has no
+    /// events.
+    pub fn get_tip(&self, id: &IdentifierPrefix) -> Result<Option<TelTip>, Error> {
+        self.load_tip(&self.tel_tips, id, || {
+            self.get_events(id).map(|events| events.collect())
+        })
+    }
+
     pub fn add_new_management_event(
         &self,
         event: VerifiableEvent,
         id: &IdentifierPrefix,
     ) -> Result<(), Error> {
-        Ok(self
-            .management_events
-            .push(self.identifiers.designated_key(id), event.into())?)
+        let tip = TelTip::append(self.get_management_tip(id)?.as_ref(), &event)?;
+        self.management_events
+            .push(self.identifiers.designated_key(id), event.into())?;
+        self.set_tip(&self.management_tips, id, Some(tip))
+    }
+
+    /// Replaces all stored management events of given identifier.
+    pub fn set_management_events(
+        &self,
+        events: Vec<VerifiableEvent>,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        let tip = TelTip::of(&events)?;
+        self.management_events
+            .put(self.identifiers.designated_key(id), events)?;
+        self.set_tip(&self.management_tips, id, tip)
+    }
+
+    /// Returns tip of management tel of given identifier, or `None` if it
+    /// has no events.
+    pub fn get_management_tip(&self, id: &IdentifierPrefix) -> Result<Option<TelTip>, Error> {
+        self.load_tip(&self.management_tips, id, || {
+            self.get_management_events(id)
+                .map(|events| events.collect())
+        })
+    }
+
+    // Returns tip stored in given tree. Tip of events stored before tips
+    // were kept is computed from the events and stored.
+    fn load_tip(
+        &self,
+        tips: &sled::Tree,
+        id: &IdentifierPrefix,
+        events: impl FnOnce() -> Option<Vec<VerifiableEvent>>,
+    ) -> Result<Option<TelTip>, Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        if let Some(value) = tips.get(key)? {
+            return Ok(Some(serde_cbor::from_slice(&value)?));
+        }
+        let tip = match events() {
+            Some(events) => TelTip::of(&events)?,
+            None => None,
+        };
+        self.set_tip(tips, id, tip.clone())?;
+        Ok(tip)
+    }
+
+    fn set_tip(
+        &self,
+        tips: &sled::Tree,
+        id: &IdentifierPrefix,
+        tip: Option<TelTip>,
+    ) -> Result<(), Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        match tip {
+            Some(tip) => tips.insert(key, serde_cbor::to_vec(&tip)?)?,
+            None => tips.remove(key)?,
+        };
+        Ok(())
     }
 
     pub fn get_management_events(
@@ -64,4 +277,278 @@ impl EventDatabase {
         self.management_events
             .iter_values(self.identifiers.designated_key(id))
     }
+
+    pub fn add_escrowed_management_event(
+        &self,
+        event: VerifiableEvent,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        Ok(self
+            .escrowed_management_events
+            .push(self.identifiers.designated_key(id), event.into())?)
+    }
+
+    /// Replaces all escrowed management events of given identifier.
+    pub fn set_escrowed_management_events(
+        &self,
+        events: Vec<VerifiableEvent>,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        Ok(self
+            .escrowed_management_events
+            .put(self.identifiers.designated_key(id), events)?)
+    }
+
+    pub fn get_escrowed_management_events(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Option<impl DoubleEndedIterator<Item = VerifiableEvent>> {
+        self.escrowed_management_events
+            .iter_values(self.identifiers.designated_key(id))
+    }
+
+    pub fn add_unknown_event(
+        &self,
+        event: VerifiableEvent,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        Ok(self
+            .unknown_events
+            .push(self.identifiers.designated_key(id), event.into())?)
+    }
+
+    pub fn get_unknown_events(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Option<impl DoubleEndedIterator<Item = VerifiableEvent>> {
+        self.unknown_events
+            .iter_values(self.identifiers.designated_key(id))
+    }
+
+    /// Stores management state as checkpoint of registry, replacing the
+    /// previous one.
+    pub fn set_management_checkpoint(
+        &self,
+        state: &ManagerTelState,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        self.management_checkpoints.insert(key, state.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn get_management_checkpoint(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<ManagerTelState>, Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        match self.management_checkpoints.get(key)? {
+            Some(value) => Ok(Some(ManagerTelState::from_bytes(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn remove_management_checkpoint(&self, id: &IdentifierPrefix) -> Result<(), Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        self.management_checkpoints.remove(key)?;
+        Ok(())
+    }
+
+    /// Returns bytes stored under identifier key in "tels" tree, without
+    /// deserializing them.
+    pub fn get_raw_events(&self, id: &IdentifierPrefix) -> Result<Option<Vec<u8>>, Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        Ok(self.raw_tel_events.get(key)?.map(|value| value.to_vec()))
+    }
+
+    /// Returns bytes stored under identifier key in "mans" tree, without
+    /// deserializing them.
+    pub fn get_raw_management_events(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = self.identifiers.designated_key(id).to_be_bytes();
+        Ok(self
+            .raw_management_events
+            .get(key)?
+            .map(|value| value.to_vec()))
+    }
+}
+
+/// Metadata of tel events stored under one identifier, updated on each
+/// write, so it's available without reading and deserializing the events.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TelTip {
+    /// Number of stored events.
+    pub count: u64,
+    /// Digest chained over all stored events, with their source seals. It
+    /// changes whenever any stored event does.
+    pub digest: SelfAddressingPrefix,
+    /// The latest stored event.
+    pub last: VerifiableEvent,
+}
+
+impl TelTip {
+    // Computes tip of events of given tip followed by event.
+    fn append(tip: Option<&TelTip>, event: &VerifiableEvent) -> Result<Self, Error> {
+        let mut data = tip
+            .map(|tip| tip.digest.to_str().into_bytes())
+            .unwrap_or_default();
+        data.extend(event.serialize()?);
+        Ok(Self {
+            count: tip.map(|tip| tip.count).unwrap_or(0) + 1,
+            digest: SelfAddressing::Blake3_256.derive(&data),
+            last: event.clone(),
+        })
+    }
+
+    // Computes tip of given events, `None` if there are none.
+    fn of(events: &[VerifiableEvent]) -> Result<Option<Self>, Error> {
+        events.iter().try_fold(None, |tip, event| {
+            Self::append(tip.as_ref(), event).map(Some)
+        })
+    }
+}
+
+// Maps failure of opening database to error describing its cause.
+fn open_error(path: &Path, e: sled::Error) -> Error {
+    match e {
+        // Sled reports failure of acquiring file lock as generic io error.
+        sled::Error::Io(io)
+            if io.kind() == ErrorKind::WouldBlock
+                || io.to_string().contains("could not acquire lock") =>
+        {
+            Error::DatabaseLocked(path.to_path_buf())
+        }
+        sled::Error::Io(io) => Error::DatabaseIo(io),
+        e => e.into(),
+    }
+}
+
+#[test]
+fn test_locked_database() -> Result<(), Error> {
+    let root = tempfile::Builder::new()
+        .prefix("test-db")
+        .tempdir()
+        .unwrap();
+    let _db = EventDatabase::new(root.path())?;
+    assert!(matches!(
+        EventDatabase::new(root.path()),
+        Err(Error::DatabaseLocked(path)) if path == root.path()
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_tel_tip() -> Result<(), Error> {
+    use crate::{
+        tel::event_generator,
+        test_utils::{dummy_source_seal, test_db},
+    };
+
+    let (_root, db) = test_db();
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let id = vcp.get_prefix();
+    let event = VerifiableEvent::new(vcp, dummy_source_seal()?.into());
+    assert_eq!(db.get_management_tip(&id)?, None);
+
+    db.add_new_management_event(event.clone(), &id)?;
+    let tip = db.get_management_tip(&id)?.unwrap();
+    assert_eq!(tip.count, 1);
+    assert_eq!(tip.last, event);
+
+    // Replacing events with the same events gives the same tip.
+    db.set_management_events(vec![event.clone()], &id)?;
+    assert_eq!(db.get_management_tip(&id)?, Some(tip.clone()));
+
+    // Digest changes with stored events.
+    db.set_management_events(vec![VerifiableEvent::unanchored(event.event)], &id)?;
+    assert_ne!(db.get_management_tip(&id)?.unwrap().digest, tip.digest);
+
+    db.clear()?;
+    assert_eq!(db.get_management_tip(&id)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_registry_vcs() -> Result<(), Error> {
+    use crate::{
+        tel::event_generator,
+        test_utils::{dummy_source_seal, make_inception, test_db},
+    };
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let (root, db) = test_db();
+    let seal = dummy_source_seal()?;
+    let (_, st) = make_inception("DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+    let mut vc_ids = vec![];
+    for message in &["first vc", "second vc"] {
+        let vc_hash = SelfAddressing::Blake3_256.derive(message.as_bytes());
+        let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+        let vc_id = IdentifierPrefix::SelfAddressing(vc_hash);
+        db.add_new_event(VerifiableEvent::new(iss, seal.clone().into()), &vc_id)?;
+        vc_ids.push(vc_id);
+    }
+    assert_eq!(db.get_registry_vcs(&st.prefix)?, vc_ids);
+
+    // Vc which events are removed isn't listed.
+    db.set_events(vec![], &vc_ids[0])?;
+    assert_eq!(db.get_registry_vcs(&st.prefix)?, vec![vc_ids[1].clone()]);
+
+    // Index missing in database is rebuilt when it's opened.
+    db.registry_vcs.clear()?;
+    drop(db);
+    let db = EventDatabase::new(root.path())?;
+    assert_eq!(db.get_registry_vcs(&st.prefix)?, vec![vc_ids[1].clone()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_error() -> Result<(), Error> {
+    use crate::{
+        id::CredentialId,
+        processor::EventProcessor,
+        state::vc_state::TelState,
+        tel::event_generator,
+        test_utils::{dummy_source_seal, make_inception, test_db},
+    };
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let seal = dummy_source_seal()?;
+    let (vcp, st) = make_inception("DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+    processor.process(VerifiableEvent::new(vcp, seal.clone().into()))?;
+    let vc_hash = SelfAddressing::Blake3_256.derive(b"some vc");
+    let vc_id = IdentifierPrefix::SelfAddressing(vc_hash.clone());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+
+    // Value under vc key can't be decoded, so event can't be appended.
+    let key = db.identifiers.designated_key(&vc_id).to_be_bytes();
+    db.raw_tel_events.insert(key, b"garbage".to_vec())?;
+    assert!(processor
+        .process(VerifiableEvent::new(iss.clone(), seal.clone().into()))
+        .is_err());
+
+    // Failed write leaves no state behind.
+    assert_eq!(db.get_tip(&vc_id)?, None);
+    assert!(db.get_registry_vcs(&st.prefix)?.is_empty());
+    db.raw_tel_events.remove(key)?;
+    processor.process(VerifiableEvent::new(iss, seal.into()))?;
+    assert!(matches!(
+        processor.get_vc_state(&CredentialId::from(vc_hash))?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
 }
@@ -1,21 +1,197 @@
-use keri::error::Error as KeriError;
+use keri::{
+    derivation::self_addressing::SelfAddressing,
+    error::Error as KeriError,
+    prefix::{IdentifierPrefix, Prefix},
+};
 use sled_tables::error::Error as SledError;
+use std::path::PathBuf;
 use thiserror::Error;
 
+/// Errors of TEL processing. `Error` is `Send + Sync + 'static`, so it can be
+/// moved between threads and returned from async tasks.
 #[derive(Error, Debug)]
 pub enum Error {
     #[error(transparent)]
-    DynError(#[from] Box<dyn std::error::Error>),
+    DynError(#[from] Box<dyn std::error::Error + Send + Sync>),
 
     #[error(transparent)]
     KeriError(#[from] KeriError),
 
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    SerdeCborError(#[from] serde_cbor::Error),
+
     #[error(transparent)]
     SledError(#[from] sled::Error),
 
     #[error(transparent)]
     SledTablesError(#[from] SledError),
 
+    #[error("Database at {} is locked by another process", .0.display())]
+    DatabaseLocked(PathBuf),
+
+    #[error("Database io error: {0}")]
+    DatabaseIo(std::io::Error),
+
+    #[error("Out of order event: expected sn {expected}, got {got}")]
+    OutOfOrder { expected: u64, got: u64 },
+
+    #[error("Unsupported event version: {major}.{minor}")]
+    UnsupportedVersion { major: u8, minor: u8 },
+
+    #[error("Event size mismatch: declared {declared}, got {actual}")]
+    SizeMismatch { declared: usize, actual: usize },
+
+    #[error("Identifier {} is blocked", .0.to_str())]
+    Blocked(IdentifierPrefix),
+
+    #[error("Event isn't anchored in KEL")]
+    MissingAnchor,
+
+    #[error("Backer threshold {threshold} exceeds number of backers {backers}")]
+    InvalidThreshold { threshold: u64, backers: usize },
+
+    #[error("Can't revoke vc which wasn't issued")]
+    CannotRevokeUnissued,
+
+    #[error("Conflicting issuance of vc {}", .0.to_str())]
+    ConflictingIssuance(IdentifierPrefix),
+
+    #[error("Digest doesn't match event data")]
+    DigestMismatch,
+
+    #[error("Digest algorithm mismatch: expected {expected:?}, got {got:?}")]
+    DigestAlgorithmMismatch {
+        expected: SelfAddressing,
+        got: SelfAddressing,
+    },
+
+    #[error("Unsupported digest algorithm: {0:?}")]
+    UnsupportedDigest(SelfAddressing),
+
+    #[error("Wrong state: {0}")]
+    WrongState(String),
+
     #[error("{0}")]
     Generic(String),
 }
+
+impl Error {
+    /// Checks if processing event may succeed later, when missing events or
+    /// anchors arrive, database is released or transient io failure passes.
+    /// Other errors mean that event is invalid and should be dropped.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::OutOfOrder { .. } | Error::MissingAnchor | Error::DatabaseLocked(_) => true,
+            Error::IoError(e) | Error::DatabaseIo(e) => is_transient(e),
+            Error::DynError(_)
+            | Error::KeriError(_)
+            | Error::SerdeJsonError(_)
+            | Error::SerdeCborError(_)
+            | Error::SledError(_)
+            | Error::SledTablesError(_)
+            | Error::UnsupportedVersion { .. }
+            | Error::SizeMismatch { .. }
+            | Error::InvalidThreshold { .. }
+            | Error::CannotRevokeUnissued
+            | Error::ConflictingIssuance(_)
+            | Error::Blocked(_)
+            | Error::DigestMismatch
+            | Error::DigestAlgorithmMismatch { .. }
+            | Error::UnsupportedDigest(_)
+            | Error::WrongState(_)
+            | Error::Generic(_) => false,
+        }
+    }
+}
+
+// Checks if io operation may succeed when repeated.
+fn is_transient(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        e.kind(),
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+    )
+}
+
+#[test]
+fn test_error_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    fn assert_std_error<T: std::error::Error>() {}
+    assert_send_sync::<Error>();
+    assert_std_error::<Error>();
+
+    let boxed: Box<dyn std::error::Error + Send + Sync> =
+        Box::new(Error::Generic("some error".into()));
+    assert_eq!(boxed.to_string(), "some error");
+}
+
+#[test]
+fn test_error_display() {
+    assert_eq!(
+        format!(
+            "{}",
+            Error::OutOfOrder {
+                expected: 1,
+                got: 3
+            }
+        ),
+        "Out of order event: expected sn 1, got 3"
+    );
+    assert_eq!(
+        Error::UnsupportedVersion { major: 2, minor: 0 }.to_string(),
+        "Unsupported event version: 2.0"
+    );
+    assert_eq!(
+        Error::WrongState("Registry not incepted".into()).to_string(),
+        "Wrong state: Registry not incepted"
+    );
+}
+
+#[test]
+fn test_is_retryable() -> Result<(), Error> {
+    let retryable = vec![
+        Error::OutOfOrder {
+            expected: 1,
+            got: 3,
+        },
+        Error::MissingAnchor,
+        Error::DatabaseLocked(PathBuf::from("db")),
+        Error::DatabaseIo(std::io::Error::from(std::io::ErrorKind::Interrupted)),
+        Error::IoError(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+    ];
+    assert!(retryable.iter().all(Error::is_retryable));
+
+    let permanent = vec![
+        Error::UnsupportedVersion { major: 2, minor: 0 },
+        Error::SizeMismatch {
+            declared: 1,
+            actual: 2,
+        },
+        Error::InvalidThreshold {
+            threshold: 2,
+            backers: 1,
+        },
+        // Retrying revocation against the same state fails the same way.
+        Error::CannotRevokeUnissued,
+        Error::ConflictingIssuance("EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?),
+        Error::DigestMismatch,
+        Error::DigestAlgorithmMismatch {
+            expected: SelfAddressing::Blake3_256,
+            got: SelfAddressing::SHA3_256,
+        },
+        Error::UnsupportedDigest(SelfAddressing::SHA2_512),
+        Error::WrongState("Registry not incepted".into()),
+        Error::Generic("some error".into()),
+        Error::DatabaseIo(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+        Error::IoError(std::io::Error::from(std::io::ErrorKind::NotFound)),
+    ];
+    assert!(!permanent.iter().any(Error::is_retryable));
+
+    Ok(())
+}
@@ -1,14 +1,19 @@
-use serde::{Deserialize, Serialize};
-use serde_hex::{Compact, SerHex};
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_hex::{Compact, SerHex, SerHexOpt};
 
 use keri::{
     derivation::{self_addressing::SelfAddressing, DerivationCode},
     event::SerializationFormats,
     event_message::serialization_info::SerializationInfo,
-    prefix::{IdentifierPrefix, SelfAddressingPrefix},
+    prefix::{IdentifierPrefix, Prefix, SelfAddressingPrefix},
 };
 
-use crate::{error::Error, state::ManagerTelState};
+use crate::{
+    error::Error,
+    state::{BackerPolicy, ManagerTelState},
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ManagerTelEvent {
@@ -24,7 +29,8 @@ pub struct ManagerTelEvent {
     #[serde(rename = "s", with = "SerHex::<Compact>")]
     pub sn: u64,
 
-    #[serde(flatten, rename = "t")]
+    // Event type is written to `t` field by `ManagerEventType` tag.
+    #[serde(flatten)]
     pub event_type: ManagerEventType,
 }
 
@@ -59,45 +65,82 @@ impl ManagerTelEvent {
             .map_err(|e| Error::KeriError(e))
     }
 
+    // Check if registry prefix is digest of inception data, derived using
+    // prefix derivation.
+    pub(crate) fn has_derived_prefix(&self, vcp: &Inc) -> Result<bool, Error> {
+        match self.prefix {
+            IdentifierPrefix::SelfAddressing(ref digest) => {
+                let inception_data = DummyEvent::derive_inception_data(
+                    vcp.clone(),
+                    &digest.derivation,
+                    self.serialization_info.kind,
+                )?;
+                Ok(digest.verify_binding(&inception_data))
+            }
+            _ => Ok(false),
+        }
+    }
+
     pub fn apply_to(&self, state: &ManagerTelState) -> Result<ManagerTelState, Error> {
         match self.event_type {
             ManagerEventType::Vcp(ref vcp) => {
                 if state != &ManagerTelState::default() {
                     Err(Error::Generic("Improper manager state".into()))
+                } else if !self.has_derived_prefix(vcp)? {
+                    Err(Error::DigestMismatch)
                 } else {
-                    let backers = if vcp.config.contains(&Config::NoBackers) {
+                    let backers = if Config::is_backerless(&vcp.config) {
                         None
                     } else {
-                        Some(vcp.backers.clone())
+                        let backers = canonical_backers(vcp.backers.clone());
+                        BackerPolicy::Count(vcp.backer_threshold).validate(&backers)?;
+                        Some(backers)
                     };
                     Ok(ManagerTelState {
                         prefix: self.prefix.to_owned(),
                         sn: 0,
                         last: self.serialize()?,
                         issuer: vcp.issuer_id.clone(),
+                        config: vcp.config.clone(),
                         backers,
+                        backer_policy: BackerPolicy::Count(vcp.backer_threshold),
+                        next_backers_digest: vcp.next_backers_digest.clone(),
                     })
                 }
             }
             ManagerEventType::Vrt(ref vrt) => {
-                if state.sn + 1 == self.sn {
+                if self.prefix != state.prefix {
+                    Err(Error::WrongState(
+                        "Event prefix doesn't match registry prefix".into(),
+                    ))
+                } else if state.sn + 1 == self.sn {
                     if vrt.prev_event.verify_binding(&state.last) {
                         match state.backers {
                             Some(ref backers) => {
-                                let mut new_backers: Vec<IdentifierPrefix> = backers
-                                    .iter()
-                                    .filter(|backer| !backers.contains(backer))
-                                    .map(|x| x.to_owned())
-                                    .collect();
-                                vrt.backers_to_add
-                                    .iter()
-                                    .for_each(|ba| new_backers.push(ba.to_owned()));
+                                let new_backers = vrt.rotate_backers(backers);
+                                // Backer set has to match the one committed at inception.
+                                if let Some(ref commitment) = state.next_backers_digest {
+                                    if !commitment.verify_binding(&next_backers_data(&new_backers))
+                                    {
+                                        return Err(Error::WrongState(
+                                            "Backers don't match committed next backers".into(),
+                                        ));
+                                    }
+                                }
+                                let backer_policy = match vrt.backer_threshold {
+                                    Some(threshold) => BackerPolicy::Count(threshold),
+                                    None => state.backer_policy.clone(),
+                                };
+                                backer_policy.validate(&new_backers)?;
                                 Ok(ManagerTelState {
                                     prefix: self.prefix.to_owned(),
                                     sn: self.sn,
                                     last: self.serialize()?,
                                     backers: Some(new_backers),
                                     issuer: state.issuer.clone(),
+                                    config: state.config.clone(),
+                                    backer_policy,
+                                    next_backers_digest: None,
                                 })
                             }
                             None => Err(Error::Generic(
@@ -107,6 +150,12 @@ impl ManagerTelEvent {
                     } else {
                         Err(Error::Generic("Previous event doesn't match".to_string()))
                     }
+                } else if self.sn > state.sn + 1 {
+                    // Binding of event from the future can't be checked yet.
+                    Err(Error::OutOfOrder {
+                        expected: state.sn + 1,
+                        got: self.sn,
+                    })
                 } else {
                     Err(Error::Generic("Improper event sn".into()))
                 }
@@ -125,10 +174,69 @@ pub enum ManagerEventType {
     Vrt(Rot),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Configuration traits of registry, set in `c` field of inception event.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Config {
-    #[serde(rename = "NB")]
+    // "NB", registry doesn't use backers.
     NoBackers,
+    // "NRB", newer code of `NoBackers`.
+    NoRegistrarBackers,
+    // "RB", registry uses its own backers.
+    RegistrarBackers,
+    // "EO", only establishment events are allowed.
+    EstablishmentOnly,
+    // "DND", registry can't be delegated.
+    DoNotDelegate,
+}
+
+impl Config {
+    /// Checks if given configuration makes registry backerless.
+    pub fn is_backerless(config: &[Config]) -> bool {
+        config
+            .iter()
+            .any(|c| matches!(c, Config::NoBackers | Config::NoRegistrarBackers))
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            Config::NoBackers => "NB",
+            Config::NoRegistrarBackers => "NRB",
+            Config::RegistrarBackers => "RB",
+            Config::EstablishmentOnly => "EO",
+            Config::DoNotDelegate => "DND",
+        };
+        f.write_str(code)
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NB" => Ok(Config::NoBackers),
+            "NRB" => Ok(Config::NoRegistrarBackers),
+            "RB" => Ok(Config::RegistrarBackers),
+            "EO" => Ok(Config::EstablishmentOnly),
+            "DND" => Ok(Config::DoNotDelegate),
+            _ => Err(Error::Generic(format!("Unknown config trait: {}", s))),
+        }
+    }
+}
+
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(de::Error::custom)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -145,6 +253,27 @@ pub struct Inc {
     // list of backer identifiers for credentials associated with this registry
     #[serde(rename = "b")]
     pub backers: Vec<IdentifierPrefix>,
+
+    // commitment to backer set established by the first registry rotation
+    #[serde(rename = "nb", default, skip_serializing_if = "Option::is_none")]
+    pub next_backers_digest: Option<SelfAddressingPrefix>,
+}
+
+/// Returns data committed by `next_backers_digest` for given backer set.
+pub fn next_backers_data(backers: &[IdentifierPrefix]) -> Vec<u8> {
+    canonical_backers(backers.to_vec())
+        .iter()
+        .map(|backer| backer.to_str())
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// Sorts backers by their prefix and removes duplicates, so backer set
+/// doesn't depend on order in which backers were added.
+pub fn canonical_backers(mut backers: Vec<IdentifierPrefix>) -> Vec<IdentifierPrefix> {
+    backers.sort_by_key(|backer| backer.to_str());
+    backers.dedup();
+    backers
 }
 
 // TODO do we need this here? It's from keriox mostly.
@@ -159,7 +288,7 @@ pub(crate) struct DummyEvent {
     prefix: String,
     #[serde(rename = "s", with = "SerHex::<Compact>")]
     sn: u8,
-    #[serde(flatten, rename = "t")]
+    #[serde(flatten)]
     data: ManagerEventType,
 }
 
@@ -234,6 +363,28 @@ pub struct Rot {
     pub backers_to_add: Vec<IdentifierPrefix>,
     #[serde(rename = "br")]
     pub backers_to_remove: Vec<IdentifierPrefix>,
+    // new backer threshold, `None` keeps the current one
+    #[serde(
+        rename = "bt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "SerHexOpt::<Compact>"
+    )]
+    pub backer_threshold: Option<u64>,
+}
+
+impl Rot {
+    /// Returns backer set resulting from applying rotation to given backers.
+    pub fn rotate_backers(&self, backers: &[IdentifierPrefix]) -> Vec<IdentifierPrefix> {
+        canonical_backers(
+            backers
+                .iter()
+                .filter(|backer| !self.backers_to_remove.contains(backer))
+                .chain(self.backers_to_add.iter())
+                .cloned()
+                .collect(),
+        )
+    }
 }
 
 #[test]
@@ -251,6 +402,7 @@ fn test_serialization() -> Result<(), Error> {
         config: vec![Config::NoBackers],
         backer_threshold: 0,
         backers: vec![],
+        next_backers_digest: None,
     });
     assert_eq!(vcp.event_type, expected_event_type);
 
@@ -266,6 +418,7 @@ fn test_serialization() -> Result<(), Error> {
         config: vec![],
         backer_threshold: 1,
         backers: vec!["EXvR3p8V95W8J7Ui4-mEzZ79S-A1esAnJo1Kmzq80Jkc".parse()?],
+        next_backers_digest: None,
     });
     assert_eq!(vcp.event_type, expected_event_type);
 
@@ -281,25 +434,52 @@ fn test_serialization() -> Result<(), Error> {
         prev_event: "EY2L3ycqK9645aEeQKP941xojSiuiHsw4Y6yTW-PmsBg".parse()?,
         backers_to_add: vec![],
         backers_to_remove: vec![],
+        backer_threshold: Some(1),
     });
     assert_eq!(vrt.event_type, expected_event_type);
 
     Ok(())
 }
 
+#[test]
+fn test_event_type_field() -> Result<(), Error> {
+    let pref: IdentifierPrefix = "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?;
+    let vrt = ManagerTelEvent::new(
+        &pref,
+        3,
+        ManagerEventType::Vrt(Rot {
+            prev_event: "EY2L3ycqK9645aEeQKP941xojSiuiHsw4Y6yTW-PmsBg".parse()?,
+            backers_to_add: vec![],
+            backers_to_remove: vec![],
+            backer_threshold: None,
+        }),
+        SerializationFormats::JSON,
+    )?;
+    let serialized = String::from_utf8(vrt.serialize()?).unwrap();
+    assert_eq!(serialized.matches(r#""t":"#).count(), 1);
+    assert!(serialized.contains(r#""t":"vrt""#));
+    assert!(!serialized.contains(r#""Vrt""#));
+
+    let deserialized: ManagerTelEvent = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, vrt);
+
+    Ok(())
+}
+
 #[test]
 fn test_apply_to() -> Result<(), Error> {
     use keri::derivation::self_addressing::SelfAddressing;
     // Construct inception event
-    let pref: IdentifierPrefix = "EVohdnN33-vdNOTPYxeTQIWVzRKtzZzBoiBSGYSSnD0s".parse()?;
     let issuer_pref: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
-    let event_type = ManagerEventType::Vcp(Inc {
+    let vcp = Inc {
         issuer_id: issuer_pref.clone(),
         config: vec![],
         backer_threshold: 1,
         backers: vec![],
-    });
-    let vcp = ManagerTelEvent::new(&pref, 0, event_type, SerializationFormats::JSON)?;
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let pref = vcp.prefix.clone();
 
     let state = ManagerTelState::default();
     let state = vcp.apply_to(&state)?;
@@ -312,6 +492,7 @@ fn test_apply_to() -> Result<(), Error> {
         prev_event,
         backers_to_add: vec!["EXvR3p8V95W8J7Ui4-mEzZ79S-A1esAnJo1Kmzq80Jkc".parse()?],
         backers_to_remove: vec![],
+        backer_threshold: None,
     });
     let vrt = ManagerTelEvent::new(&pref, 1, event_type.clone(), SerializationFormats::JSON)?;
     let state = vrt.apply_to(&state)?;
@@ -328,6 +509,7 @@ fn test_apply_to() -> Result<(), Error> {
         prev_event,
         backers_to_remove: vec![],
         backers_to_add: vec![],
+        backer_threshold: None,
     });
     let bad_previous = ManagerTelEvent::new(&pref, 2, event_type, SerializationFormats::JSON)?;
     let err_state = bad_previous.apply_to(&state);
@@ -342,11 +524,70 @@ fn test_apply_to() -> Result<(), Error> {
             "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?,
             "Dvxo-P4W_Z0xXTfoA3_4DMPn7oi0mLCElOWJDpC0nQXw".parse()?,
         ],
+        backer_threshold: None,
     });
     let vrt = ManagerTelEvent::new(&pref, 2, event_type.clone(), SerializationFormats::JSON)?;
     let state = vrt.apply_to(&state)?;
     assert_eq!(state.backers.clone().unwrap().len(), 2);
 
+    // Try applying event with threshold exceeding number of backers.
+    let event_type = ManagerEventType::Vrt(Rot {
+        prev_event: SelfAddressing::Blake3_256.derive(&vrt.serialize()?),
+        backers_to_remove: vec![],
+        backers_to_add: vec![],
+        backer_threshold: Some(3),
+    });
+    let vrt = ManagerTelEvent::new(&pref, 3, event_type, SerializationFormats::JSON)?;
+    assert!(matches!(
+        vrt.apply_to(&state),
+        Err(Error::InvalidThreshold {
+            threshold: 3,
+            backers: 2
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_registry_prefix_consistency() -> Result<(), Error> {
+    use keri::derivation::self_addressing::SelfAddressing;
+    let issuer_pref: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = Inc {
+        issuer_id: issuer_pref.clone(),
+        config: vec![],
+        backer_threshold: 0,
+        backers: vec![],
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let pref = vcp.prefix.clone();
+    let state = vcp.apply_to(&ManagerTelState::default())?;
+
+    // Rotation bound to registry inception, but claiming other registry prefix.
+    let other_pref: IdentifierPrefix = "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?;
+    let event_type = ManagerEventType::Vrt(Rot {
+        prev_event: SelfAddressing::Blake3_256.derive(&vcp.serialize()?),
+        backers_to_add: vec![],
+        backers_to_remove: vec![],
+        backer_threshold: None,
+    });
+    let tampered_vrt = ManagerTelEvent::new(
+        &other_pref,
+        1,
+        event_type.clone(),
+        SerializationFormats::JSON,
+    )?;
+    assert!(matches!(
+        tampered_vrt.apply_to(&state),
+        Err(Error::WrongState(_))
+    ));
+
+    let vrt = ManagerTelEvent::new(&pref, 1, event_type, SerializationFormats::JSON)?;
+    let state = vrt.apply_to(&state)?;
+    assert_eq!(state.prefix, pref);
+    assert_eq!(state.issuer, issuer_pref);
+
     Ok(())
 }
 
@@ -354,15 +595,16 @@ fn test_apply_to() -> Result<(), Error> {
 fn test_no_backers() -> Result<(), Error> {
     use keri::derivation::self_addressing::SelfAddressing;
     // Construct inception event
-    let pref: IdentifierPrefix = "EVohdnN33-vdNOTPYxeTQIWVzRKtzZzBoiBSGYSSnD0s".parse()?;
     let issuer_pref: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
-    let event_type = ManagerEventType::Vcp(Inc {
+    let vcp = Inc {
         issuer_id: issuer_pref.clone(),
         config: vec![Config::NoBackers],
         backer_threshold: 1,
         backers: vec![],
-    });
-    let vcp = ManagerTelEvent::new(&pref, 0, event_type, SerializationFormats::JSON)?;
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let pref = vcp.prefix.clone();
 
     let state = ManagerTelState::default();
     let state = vcp.apply_to(&state)?;
@@ -375,6 +617,7 @@ fn test_no_backers() -> Result<(), Error> {
         prev_event,
         backers_to_add: vec!["EXvR3p8V95W8J7Ui4-mEzZ79S-A1esAnJo1Kmzq80Jkc".parse()?],
         backers_to_remove: vec![],
+        backer_threshold: None,
     });
     let vrt = ManagerTelEvent::new(&pref, 1, event_type.clone(), SerializationFormats::JSON)?;
     // Try to update backers of backerless state.
@@ -383,3 +626,178 @@ fn test_no_backers() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_next_backers_commitment() -> Result<(), Error> {
+    let issuer_pref: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let first_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+    let second_backer: IdentifierPrefix = "Dvxo-P4W_Z0xXTfoA3_4DMPn7oi0mLCElOWJDpC0nQXw".parse()?;
+
+    // Commit to both backers at inception.
+    let committed = vec![first_backer.clone(), second_backer.clone()];
+    let vcp = Inc {
+        issuer_id: issuer_pref,
+        config: vec![],
+        backer_threshold: 0,
+        backers: vec![],
+        next_backers_digest: Some(
+            SelfAddressing::Blake3_256.derive(&next_backers_data(&committed)),
+        ),
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let pref = vcp.prefix.clone();
+    let serialized = String::from_utf8(vcp.serialize()?).unwrap();
+    assert!(serialized.contains(r#""nb":"#));
+    let state = vcp.apply_to(&ManagerTelState::default())?;
+    assert!(state.next_backers_digest.is_some());
+
+    let prev_event = SelfAddressing::Blake3_256.derive(&vcp.serialize()?);
+    let not_committed = ManagerTelEvent::new(
+        &pref,
+        1,
+        ManagerEventType::Vrt(Rot {
+            prev_event: prev_event.clone(),
+            backers_to_add: vec![first_backer],
+            backers_to_remove: vec![],
+            backer_threshold: None,
+        }),
+        SerializationFormats::JSON,
+    )?;
+    assert!(matches!(
+        not_committed.apply_to(&state),
+        Err(Error::WrongState(_))
+    ));
+
+    let committed_vrt = ManagerTelEvent::new(
+        &pref,
+        1,
+        ManagerEventType::Vrt(Rot {
+            prev_event,
+            backers_to_add: committed.clone(),
+            backers_to_remove: vec![],
+            backer_threshold: None,
+        }),
+        SerializationFormats::JSON,
+    )?;
+    let state = committed_vrt.apply_to(&state)?;
+    assert_eq!(state.backers, Some(committed));
+    assert_eq!(state.next_backers_digest, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_vcp_prefix_derivation() -> Result<(), Error> {
+    let vcp = Inc {
+        issuer_id: "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        config: vec![],
+        backer_threshold: 0,
+        backers: vec![],
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    assert!(vcp.apply_to(&ManagerTelState::default()).is_ok());
+
+    let tampered_vcp = ManagerTelEvent::new(
+        &"EVohdnN33-vdNOTPYxeTQIWVzRKtzZzBoiBSGYSSnD0s".parse()?,
+        0,
+        vcp.event_type.clone(),
+        SerializationFormats::JSON,
+    )?;
+    assert!(matches!(
+        tampered_vcp.apply_to(&ManagerTelState::default()),
+        Err(Error::DigestMismatch)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_canonical_backers() -> Result<(), Error> {
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let first_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+    let second_backer: IdentifierPrefix = "Dvxo-P4W_Z0xXTfoA3_4DMPn7oi0mLCElOWJDpC0nQXw".parse()?;
+    let third_backer: IdentifierPrefix = "EXvR3p8V95W8J7Ui4-mEzZ79S-A1esAnJo1Kmzq80Jkc".parse()?;
+    let vcp = Inc {
+        issuer_id: "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        config: vec![],
+        backer_threshold: 0,
+        backers: vec![third_backer.clone()],
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let state = vcp.apply_to(&ManagerTelState::default())?;
+    let rotate = |backers_to_add: Vec<IdentifierPrefix>| -> Result<ManagerTelState, Error> {
+        let vrt = ManagerTelEvent::new(
+            &vcp.prefix,
+            1,
+            ManagerEventType::Vrt(Rot {
+                prev_event: SelfAddressing::Blake3_256.derive(&vcp.serialize()?),
+                backers_to_add,
+                backers_to_remove: vec![],
+                backer_threshold: None,
+            }),
+            SerializationFormats::JSON,
+        )?;
+        vrt.apply_to(&state)
+    };
+
+    let added_in_order = rotate(vec![first_backer.clone(), second_backer.clone()])?;
+    let added_in_reverse = rotate(vec![second_backer.clone(), first_backer.clone()])?;
+    assert_eq!(added_in_order.backers, added_in_reverse.backers);
+    assert_eq!(
+        added_in_order.backers,
+        Some(vec![
+            first_backer.clone(),
+            second_backer.clone(),
+            third_backer
+        ])
+    );
+    assert_eq!(
+        next_backers_data(&[second_backer.clone(), first_backer.clone()]),
+        next_backers_data(&[first_backer, second_backer])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_config() -> Result<(), Error> {
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let all = vec![
+        Config::NoBackers,
+        Config::NoRegistrarBackers,
+        Config::RegistrarBackers,
+        Config::EstablishmentOnly,
+        Config::DoNotDelegate,
+    ];
+    for config in &all {
+        assert_eq!(&config.to_string().parse::<Config>()?, config);
+    }
+    assert!("XX".parse::<Config>().is_err());
+
+    let config = vec![
+        Config::NoRegistrarBackers,
+        Config::EstablishmentOnly,
+        Config::DoNotDelegate,
+    ];
+    let vcp = Inc {
+        issuer_id: "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        config: config.clone(),
+        backer_threshold: 0,
+        backers: vec![],
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let vcp_raw = String::from_utf8(vcp.serialize()?).unwrap();
+    assert!(vcp_raw.contains(r#""c":["NRB","EO","DND"]"#));
+
+    let parsed: ManagerTelEvent = serde_json::from_str(&vcp_raw)?;
+    let state = parsed.apply_to(&ManagerTelState::default())?;
+    assert_eq!(state.config, config);
+    assert!(state.is_backerless());
+
+    Ok(())
+}
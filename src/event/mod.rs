@@ -1,31 +1,55 @@
 use crate::error::Error;
 
-use self::{manager_event::ManagerTelEvent, vc_event::VCEvent};
-use keri::prefix::IdentifierPrefix;
-use serde::{Deserialize, Serialize};
+use self::{manager_event::ManagerTelEvent, vc_event::TimestampedVCEvent};
+use keri::{
+    event::SerializationFormats, event_message::serialization_info::SerializationInfo,
+    prefix::IdentifierPrefix,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 pub mod manager_event;
+pub mod parse;
+pub mod serializer;
 pub mod vc_event;
 pub mod verifiable_event;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Event {
     Management(ManagerTelEvent),
-    Vc(VCEvent),
+    Vc(TimestampedVCEvent),
+    // Event of type not known to this version of the library. It is kept as
+    // it was received, so it can be stored and forwarded, but never applied
+    // to state.
+    Unknown { t: String, raw: Vec<u8> },
 }
 
 impl Event {
     pub fn get_prefix(&self) -> IdentifierPrefix {
         match self {
             Event::Management(man) => man.prefix.clone(),
-            Event::Vc(ev) => ev.prefix.clone(),
+            Event::Vc(ev) => ev.event.prefix.clone(),
+            Event::Unknown { raw, .. } => raw_field(raw, "i").unwrap_or_default(),
         }
     }
 
     pub fn get_sn(&self) -> u64 {
         match self {
             Event::Management(man) => man.sn,
-            Event::Vc(ev) => ev.sn,
+            Event::Vc(ev) => ev.event.sn,
+            Event::Unknown { raw, .. } => raw_field::<String>(raw, "s")
+                .and_then(|sn| u64::from_str_radix(&sn, 16).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn get_serialization_info(&self) -> SerializationInfo {
+        match self {
+            Event::Management(man) => man.serialization_info.clone(),
+            Event::Vc(ev) => ev.serialization_info(),
+            Event::Unknown { raw, .. } => raw_field(raw, "v").unwrap_or_else(|| {
+                let format = raw_format(raw).unwrap_or(SerializationFormats::JSON);
+                SerializationInfo::new(format, raw.len())
+            }),
         }
     }
 
@@ -33,6 +57,58 @@ impl Event {
         match self {
             Event::Management(man) => man.serialize(),
             Event::Vc(ev) => ev.serialize(),
+            Event::Unknown { raw, .. } => Ok(raw.clone()),
+        }
+    }
+
+    /// Returns bytes bound by `p` digest of the following event. Vc events
+    /// are bound without their `dt` timestamp.
+    pub fn chained_bytes(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            Event::Vc(ev) => ev.event.serialize(),
+            event => event.serialize(),
         }
     }
 }
+
+// Reads field of event serialized in any of supported formats.
+fn raw_field<T: DeserializeOwned>(raw: &[u8], name: &str) -> Option<T> {
+    let event: serde_json::Value = match raw_format(raw)? {
+        SerializationFormats::JSON => serde_json::from_slice(raw).ok()?,
+        SerializationFormats::CBOR => serde_cbor::from_slice(raw).ok()?,
+        SerializationFormats::MGPK => rmp_serde::from_slice(raw).ok()?,
+    };
+    serde_json::from_value(event.get(name)?.clone()).ok()
+}
+
+// Recognizes serialization format of event by its first byte, which opens
+// a map in each of the formats.
+fn raw_format(raw: &[u8]) -> Option<SerializationFormats> {
+    match raw.first()? {
+        b'{' => Some(SerializationFormats::JSON),
+        0xa0..=0xbf => Some(SerializationFormats::CBOR),
+        0x80..=0x8f | 0xde | 0xdf => Some(SerializationFormats::MGPK),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_unknown_cbor_event() -> Result<(), Error> {
+    let json = r#"{"v":"KERI10CBOR000000_","i":"EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs","s":"a","t":"vdp"}"#;
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let event = Event::Unknown {
+        t: "vdp".into(),
+        raw: serde_cbor::to_vec(&value)?,
+    };
+    assert_eq!(event.get_sn(), 10);
+    assert_eq!(
+        event.get_prefix(),
+        "EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs".parse()?
+    );
+    assert!(matches!(
+        event.get_serialization_info().kind,
+        SerializationFormats::CBOR
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,255 @@
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    event::{
+        manager_event::ManagerTelEvent, vc_event::TimestampedVCEvent,
+        verifiable_event::VerifiableEvent,
+    },
+    seal::{parse::event_source_seal, AttachedSourceSeal},
+};
+
+use super::Event;
+
+/// Serialize verifiable event into KERI style frame: event body followed by
+/// its source seal attachment.
+pub fn tel_event_to_cesr(event: &VerifiableEvent) -> Result<Vec<u8>, Error> {
+    event.serialize()
+}
+
+/// Extracts TEL events from stream of KERI style frames.
+///
+/// Frames of TEL events (`vcp`, `vrt`, `iss`, `rev`, `bis`, `brv`) are parsed
+/// into `VerifiableEvent`s. Any other frames (for example KEL events with
+/// their attachments) are skipped. Only JSON serialized events are supported.
+pub fn parse_tel_stream(stream: &[u8]) -> Result<Vec<VerifiableEvent>, Error> {
+    let mut rest = stream;
+    let mut events = vec![];
+    while !rest.iter().all(|b| b.is_ascii_whitespace()) {
+        let (tail, event) = tel_frame(rest)?;
+        if let Some(event) = event {
+            events.push(event);
+        }
+        rest = tail;
+    }
+    Ok(events)
+}
+
+/// Parses single JSON serialized TEL event, without attachments.
+/// Events of unknown type are returned as `Event::Unknown`.
+pub fn parse_event(data: &[u8]) -> Result<Event, Error> {
+    match event_body(data)? {
+        (rest, Some(event)) if rest.is_empty() => Ok(event),
+        (_, Some(_)) => Err(Error::Generic("Unexpected data after event".into())),
+        (_, None) => Err(Error::Generic("Not a TEL event".into())),
+    }
+}
+
+/// Parses event from untrusted data. Any malformed input results in error.
+pub fn try_parse_event(data: &[u8]) -> Result<Event, Error> {
+    parse_event(data)
+}
+
+// Fields holding identifiers and digests, at any nesting level.
+const PREFIX_FIELDS: &[&str] = &["i", "ii", "ri", "p", "nb", "d", "b", "ba", "br"];
+
+// Checks fields which keri parsers slice by position, so malformed data
+// results in error instead of reaching them: version string has to be
+// complete and identifiers have to be long enough to hold derivation code.
+fn validate_fields(value: &Value) -> Result<(), Error> {
+    match value.get("v") {
+        Some(Value::String(v))
+            if v.len() == 17 && v.is_ascii() && v.starts_with("KERI") && v.ends_with('_') => {}
+        Some(_) => return Err(Error::Generic("Malformed version string".into())),
+        None => (),
+    }
+    validate_prefixes(value)
+}
+
+fn validate_prefixes(value: &Value) -> Result<(), Error> {
+    let fields = match value {
+        Value::Object(fields) => fields,
+        _ => return Ok(()),
+    };
+    for (name, field) in fields {
+        if !PREFIX_FIELDS.contains(&name.as_str()) {
+            validate_prefixes(field)?;
+            continue;
+        }
+        let prefixes = match field {
+            Value::Array(items) => items.iter().collect(),
+            field => vec![field],
+        };
+        for prefix in prefixes {
+            match prefix {
+                Value::String(prefix) if prefix.len() < 4 || !prefix.is_ascii() => {
+                    return Err(Error::Generic(format!("Malformed identifier in {}", name)))
+                }
+                // Values of other types are rejected by deserializer.
+                _ => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+// Parse event body at the beginning of data. Returns `None` if it has no
+// event type.
+fn event_body(data: &[u8]) -> Result<(&[u8], Option<Event>), Error> {
+    let mut stream = serde_json::Deserializer::from_slice(data).into_iter::<Value>();
+    let value = match stream.next() {
+        Some(value) => value?,
+        None => return Err(Error::Generic("Empty stream".into())),
+    };
+    let (body, rest) = data.split_at(stream.byte_offset());
+
+    let event = match value.get("t").and_then(Value::as_str) {
+        Some("vcp") | Some("vrt") => {
+            validate_fields(&value)?;
+            Some(Event::Management(
+                serde_json::from_slice::<ManagerTelEvent>(body)?,
+            ))
+        }
+        Some("iss") | Some("rev") | Some("bis") | Some("brv") => {
+            validate_fields(&value)?;
+            Some(Event::Vc(serde_json::from_slice::<TimestampedVCEvent>(
+                body,
+            )?))
+        }
+        Some(t) => Some(Event::Unknown {
+            t: t.to_string(),
+            raw: body.to_vec(),
+        }),
+        None => None,
+    };
+    Ok((rest, event))
+}
+
+// Parse single frame. Returns `None` if frame doesn't contain TEL event.
+fn tel_frame(data: &[u8]) -> Result<(&[u8], Option<VerifiableEvent>), Error> {
+    let (rest, event) = match event_body(data)? {
+        (rest, Some(Event::Unknown { .. })) | (rest, None) => {
+            // Not a TEL event. Skip its attachments up to the next frame.
+            let next_frame = rest.iter().position(|b| *b == b'{').unwrap_or(rest.len());
+            return Ok((&rest[next_frame..], None));
+        }
+        (rest, Some(event)) => (rest, event),
+    };
+
+    let (rest, seal) = match rest.split_first() {
+        Some((b'-', attachment)) => event_source_seal(attachment)
+            .map_err(|_| Error::Generic("Improper source seal attachment".into()))?,
+        _ => return Err(Error::Generic("Missing source seal attachment".into())),
+    };
+    Ok((
+        rest,
+        Some(VerifiableEvent::new(event, AttachedSourceSeal::from(seal))),
+    ))
+}
+
+#[test]
+fn test_parse_tel_stream() -> Result<(), Error> {
+    use crate::{seal::EventSourceSeal, state::ManagerTelState, tel::event_generator};
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let source_seal = EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    };
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+    let verifiable_vcp = VerifiableEvent::new(vcp.clone(), source_seal.clone().into());
+
+    let state = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => unreachable!(),
+    };
+    let vc_hash = SelfAddressing::Blake3_256.derive("some message".as_bytes());
+    let iss = event_generator::make_issuance_event(&state, vc_hash, None, None)?;
+    let verifiable_iss = VerifiableEvent::new(iss, source_seal.into());
+
+    let icp_frame = r#"{"v":"KERI10JSON0000ed_","i":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"0","t":"icp","kt":"1","k":["DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM"],"n":"","wt":"0","w":[],"c":[]}-AABAAE0ZHKp7ydEqRQ8hPGjvdvU0SUppz3kXU5vY5b3dAMdhCrUETAqLBeEyYZRovE0JKl-k1fIXWzEaWrGv3VJ1BQ"#;
+    let ixn_frame = r#"{"v":"KERI10JSON000098_","i":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"1","t":"ixn","p":"EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8","a":[]}-AABAAE0ZHKp7ydEqRQ8hPGjvdvU0SUppz3kXU5vY5b3dAMdhCrUETAqLBeEyYZRovE0JKl-k1fIXWzEaWrGv3VJ1BQ"#;
+
+    let stream = [
+        icp_frame.as_bytes().to_vec(),
+        tel_event_to_cesr(&verifiable_vcp)?,
+        ixn_frame.as_bytes().to_vec(),
+        tel_event_to_cesr(&verifiable_iss)?,
+    ]
+    .concat();
+
+    let events = parse_tel_stream(&stream)?;
+    assert_eq!(events, vec![verifiable_vcp, verifiable_iss]);
+
+    // TEL event without source seal attachment.
+    let missing_attachment = [ixn_frame.as_bytes(), &vcp.serialize()?].concat();
+    assert!(parse_tel_stream(&missing_attachment).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_malformed_event() -> Result<(), Error> {
+    use crate::tel::event_generator;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+    let serialized = vcp.serialize()?;
+    assert_eq!(try_parse_event(&serialized)?, vcp);
+
+    // Truncated event.
+    for end in 0..serialized.len() {
+        assert!(try_parse_event(&serialized[..end]).is_err());
+    }
+
+    // Event with corrupted bytes.
+    for i in 0..serialized.len() {
+        let mut corrupted = serialized.clone();
+        corrupted[i] = 0xff;
+        assert!(try_parse_event(&corrupted).is_err());
+    }
+
+    // Garbage and fields of improper shape.
+    let garbage: &[&[u8]] = &[
+        b"",
+        b"\x00\x01\x02",
+        b"{}",
+        b"[]",
+        b"null",
+        br#"{"t":"vcp"}"#,
+        br#"{"v":"","i":"","s":"","t":"iss","ri":""}"#,
+        br#"{"v":"KERI10JSON","i":"E","s":"zz","t":"rev","p":"E"}"#,
+        br#"{"v":"KERI10JSON00011c_","i":"E","s":"0","t":"bis","ra":{},"dt":"yesterday"}"#,
+        br#"{"v":"KERI10JSON0000ad_","i":"0","ii":"1","s":"0","t":"vcp","c":[],"bt":"0","b":[]}"#,
+        br#"{"v":"KERI10JSON0000ad_","i":"\u00e9\u00e9\u00e9\u00e9","s":"0","t":"iss","ri":"E"}"#,
+        br#"{"v":"KERI\u00e9JSON0000ad_","i":"E","s":"0","t":"rev"}"#,
+        br#"{"v":"KERI10JSON0000ad_","i":"E","s":"0","t":"vrt","p":"","ba":["B"],"br":[]}"#,
+    ];
+    for data in garbage {
+        assert!(try_parse_event(data).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_unknown_event() -> Result<(), Error> {
+    let raw = r#"{"v":"KERI10JSON000092_","i":"EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs","s":"a","t":"vdp","di":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM"}"#;
+    let event = parse_event(raw.as_bytes())?;
+    assert_eq!(
+        event,
+        Event::Unknown {
+            t: "vdp".into(),
+            raw: raw.as_bytes().to_vec()
+        }
+    );
+    assert_eq!(event.get_sn(), 10);
+    assert_eq!(
+        event.get_prefix(),
+        "EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs".parse()?
+    );
+    assert_eq!(event.serialize()?, raw.as_bytes());
+
+    Ok(())
+}
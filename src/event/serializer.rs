@@ -0,0 +1,85 @@
+use crate::error::Error;
+
+use super::{parse, Event};
+
+/// Output encoding of TEL events.
+///
+/// Allows to replace the default encoding, which uses serialization format
+/// declared in event's version string, of events returned by processor and
+/// parsed by its `parse_event`. It only formats output: event digests,
+/// declared sizes and `p` bindings are always computed from the version
+/// string encoding, which is also how events are stored.
+pub trait TelSerializer {
+    fn serialize(&self, event: &Event) -> Result<Vec<u8>, Error>;
+    fn deserialize(&self, data: &[u8]) -> Result<Event, Error>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct DefaultSerializer;
+
+impl TelSerializer for DefaultSerializer {
+    fn serialize(&self, event: &Event) -> Result<Vec<u8>, Error> {
+        event.serialize()
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Event, Error> {
+        parse::parse_event(data)
+    }
+}
+
+#[test]
+fn test_custom_serializer() -> Result<(), Error> {
+    use crate::{
+        database::EventDatabase, event::verifiable_event::VerifiableEvent, seal::EventSourceSeal,
+        tel::Tel,
+    };
+    use std::{cell::Cell, fs};
+    use tempfile::Builder;
+
+    // Serializer delegating to the default one, which counts its usage.
+    #[derive(Default)]
+    struct CountingSerializer {
+        serialized: Cell<usize>,
+    }
+
+    impl TelSerializer for CountingSerializer {
+        fn serialize(&self, event: &Event) -> Result<Vec<u8>, Error> {
+            self.serialized.set(self.serialized.get() + 1);
+            DefaultSerializer.serialize(event)
+        }
+
+        fn deserialize(&self, data: &[u8]) -> Result<Event, Error> {
+            DefaultSerializer.deserialize(data)
+        }
+    }
+
+    let root = Builder::new().prefix("test-db").tempdir().unwrap();
+    fs::create_dir_all(root.path()).unwrap();
+    let db = EventDatabase::new(root.path()).unwrap();
+    let mut tel = Tel::with_serializer(&db, CountingSerializer::default());
+
+    let issuer_prefix = "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?;
+    let dummy_source_seal = EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    };
+    let vcp = tel.make_inception_event(issuer_prefix, vec![], 0, vec![])?;
+    let registry_id = vcp.get_prefix();
+    tel.process(VerifiableEvent::new(vcp.clone(), dummy_source_seal.into()))?;
+
+    // Event round-trips through custom serializer.
+    let serializer = CountingSerializer::default();
+    assert_eq!(serializer.deserialize(&serializer.serialize(&vcp)?)?, vcp);
+
+    // Processor uses custom serializer for its output.
+    let serialized = tel
+        .processor
+        .get_management_events(&registry_id.into())?
+        .unwrap();
+    assert_eq!(tel.processor.serializer().serialized.get(), 1);
+    let parsed = parse::parse_tel_stream(&serialized)?;
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed[0].event, vcp);
+
+    Ok(())
+}
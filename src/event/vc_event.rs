@@ -1,5 +1,5 @@
 use crate::error::Error;
-use chrono::{DateTime, FixedOffset, Local, SecondsFormat};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Timelike};
 use keri::{
     event::{sections::seal::EventSeal, SerializationFormats},
     event_message::serialization_info::SerializationInfo,
@@ -8,6 +8,10 @@ use keri::{
 use serde::{de, Deserialize, Serialize, Serializer};
 use serde_hex::{Compact, SerHex};
 
+/// Vc event with `dt` timestamp field. Event keeps version string it was
+/// received with, so `p` of the following event binds the event exactly as
+/// received, without `dt` field. Timestamp itself is bound only by KEL seal
+/// made over the whole event.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TimestampedVCEvent {
     #[serde(flatten)]
@@ -33,18 +37,48 @@ fn timestamp_deserialize<'de, D>(deserializer: D) -> Result<DateTime<Local>, D::
 where
     D: de::Deserializer<'de>,
 {
-    let s: &str = de::Deserialize::deserialize(deserializer)?;
-    // serde_json::from_str(s).map_err(de::Error::custom)
-    let dt: DateTime<Local> = DateTime::from(chrono::DateTime::parse_from_rfc3339(s).unwrap());
-    Ok(dt)
+    let s: String = de::Deserialize::deserialize(deserializer)?;
+    let dt = chrono::DateTime::parse_from_rfc3339(&s).map_err(de::Error::custom)?;
+    Ok(DateTime::from(dt))
 }
 
 impl TimestampedVCEvent {
-    pub fn new(event: VCEvent) -> Self {
-        Self {
-            timestamp: Local::now(),
-            event,
+    pub fn new(event: VCEvent) -> Result<Self, Error> {
+        Self::with_timestamp(event, Local::now())
+    }
+
+    /// Attaches timestamp to event and updates event size, so it covers `dt`
+    /// field. Timestamp is serialized with seconds precision, so fractions of
+    /// seconds are dropped.
+    pub fn with_timestamp(mut event: VCEvent, timestamp: DateTime<Local>) -> Result<Self, Error> {
+        let timestamp = timestamp.with_nanosecond(0).unwrap_or(timestamp);
+        let format = event.serialization_info.kind;
+        event.serialization_info = SerializationInfo::new(format, 0);
+        let size = Self {
+            event: event.clone(),
+            timestamp,
         }
+        .serialize()?
+        .len();
+        event.serialization_info = SerializationInfo::new(format, size);
+        Ok(Self { event, timestamp })
+    }
+
+    /// Returns version string of the whole event, including `dt` field.
+    pub fn serialization_info(&self) -> SerializationInfo {
+        self.event.serialization_info.clone()
+    }
+
+    pub fn timestamp(&self) -> DateTime<Local> {
+        self.timestamp
+    }
+
+    pub fn serialize(&self) -> Result<Vec<u8>, Error> {
+        self.event
+            .serialization_info
+            .kind
+            .encode(self)
+            .map_err(|e| Error::KeriError(e))
     }
 }
 
@@ -100,6 +134,28 @@ impl VCEvent {
             .encode(self)
             .map_err(|e| Error::KeriError(e))
     }
+
+    /// Returns vc prefix as digest of vc content, or `None` if event prefix
+    /// isn't self addressing.
+    pub fn vc_digest(&self) -> Option<SelfAddressingPrefix> {
+        match self.prefix {
+            IdentifierPrefix::SelfAddressing(ref digest) => Some(digest.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns identifier of registry referenced by event, if event contains it.
+    pub fn registry_id(&self) -> Option<IdentifierPrefix> {
+        match self.event_type {
+            VCEventType::Iss(ref iss) => Some(iss.registry_id.clone()),
+            VCEventType::Bis(ref iss) => Some(iss.registry_anchor.prefix.clone()),
+            VCEventType::Brv(ref rev) => rev
+                .registry_anchor
+                .as_ref()
+                .map(|anchor| anchor.prefix.clone()),
+            VCEventType::Rev(_) => None,
+        }
+    }
 }
 
 // #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -115,8 +171,10 @@ pub enum VCEventType {
 }
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Issuance {
+    // registry anchor to management TEL, serialized as KERI event seal:
+    // `{"i": prefix, "s": hex sn, "d": event digest}`
     #[serde(rename = "ra")]
-    registry_anchor: EventSeal,
+    pub registry_anchor: EventSeal,
 }
 
 impl Issuance {
@@ -167,3 +225,97 @@ fn test_tel_event_serialization() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_registry_anchor_serialization() -> Result<(), Error> {
+    let ra_raw = r#"{"i":"EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw","s":"3","d":"Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4"}"#;
+    let bis_raw = [
+        r#"{"v":"KERI10JSON000126_","i":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"0","t":"bis","ra":"#,
+        ra_raw,
+        r#","dt":"2021-01-01T00:00:00+00:00"}"#,
+    ]
+    .concat();
+    let bis_ev: TimestampedVCEvent = serde_json::from_str(&bis_raw)?;
+    assert_eq!(serde_json::to_string(&bis_ev)?, bis_raw);
+
+    let registry_anchor = match bis_ev.event.event_type {
+        VCEventType::Bis(ref iss) => iss.registry_anchor.clone(),
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    assert_eq!(
+        registry_anchor.prefix,
+        "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?
+    );
+    assert_eq!(registry_anchor.sn, 3);
+    assert_eq!(
+        registry_anchor.event_digest,
+        "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?
+    );
+    assert_eq!(serde_json::to_string(&registry_anchor)?, ra_raw);
+
+    // Sn is compact hex.
+    let anchor = EventSeal {
+        sn: 26,
+        ..registry_anchor
+    };
+    assert!(serde_json::to_string(&anchor)?.contains(r#""s":"1a""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_vc_digest() -> Result<(), Error> {
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let registry_id: IdentifierPrefix = "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?;
+    let iss = VCEvent::new(
+        IdentifierPrefix::SelfAddressing(vc_hash.clone()),
+        0,
+        VCEventType::Iss(SimpleIssuance { registry_id }),
+        SerializationFormats::JSON,
+    )?;
+    assert_eq!(iss.vc_digest(), Some(vc_hash.clone()));
+
+    let rev = VCEvent::new(
+        "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        1,
+        VCEventType::Rev(SimpleRevocation {
+            prev_event_hash: vc_hash,
+        }),
+        SerializationFormats::JSON,
+    )?;
+    assert_eq!(rev.vc_digest(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_timestamped_size() -> Result<(), Error> {
+    let registry_id: IdentifierPrefix = "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?;
+    let iss = VCEvent::new(
+        "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?,
+        0,
+        VCEventType::Iss(SimpleIssuance { registry_id }),
+        SerializationFormats::JSON,
+    )?;
+    let timestamped = TimestampedVCEvent::new(iss)?;
+    let raw = timestamped.serialize()?;
+
+    // Declared size covers `dt` field.
+    assert_eq!(raw.len(), timestamped.serialization_info().size);
+
+    // Parsed event keeps version string it was received with, so it
+    // serializes back to the same bytes.
+    let parsed: TimestampedVCEvent = serde_json::from_slice(&raw)?;
+    assert_eq!(parsed, timestamped);
+    assert_eq!(parsed.serialize()?, raw);
+
+    // Chained bytes are the received event without `dt` field.
+    let raw = String::from_utf8(raw).unwrap();
+    let dt_start = raw.find(r#","dt":"#).unwrap();
+    let without_dt = format!("{}}}", &raw[..dt_start]);
+    assert_eq!(parsed.event.serialize()?, without_dt.as_bytes());
+
+    Ok(())
+}
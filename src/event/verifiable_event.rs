@@ -1,30 +1,225 @@
 use crate::error::Error;
-use crate::seal::AttachedSourceSeal;
+use crate::seal::{AttachedSourceSeal, EventSourceSeal};
+use keri::{
+    derivation::self_addressing::SelfAddressing, event_message::SignedEventMessage, prefix::Prefix,
+};
 use serde::{Deserialize, Serialize};
 
-use super::Event;
+use super::{manager_event::ManagerEventType, Event};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct VerifiableEvent {
     pub event: Event,
-    pub seal: AttachedSourceSeal,
+    // Source seal of KEL event anchoring this event. `None` for local events
+    // which aren't anchored yet.
+    pub seal: Option<AttachedSourceSeal>,
 }
 
 impl VerifiableEvent {
     pub fn new(event: Event, seal: AttachedSourceSeal) -> Self {
-        Self { event, seal }
+        Self {
+            event,
+            seal: Some(seal),
+        }
+    }
+
+    /// Creates event, checking if attached source seal is well formed.
+    pub fn try_new(event: Event, seal: AttachedSourceSeal) -> Result<Self, Error> {
+        seal.seal.validate()?;
+        Ok(Self::new(event, seal))
+    }
+
+    /// Creates event without source seal attachment.
+    pub fn unanchored(event: Event) -> Self {
+        Self { event, seal: None }
+    }
+
+    /// Checks if event has source seal pointing to KEL event.
+    pub fn is_anchored(&self) -> bool {
+        match self.seal {
+            Some(ref seal) => !seal.seal.is_placeholder(),
+            None => false,
+        }
+    }
+
+    /// Checks, without looking into database or KEL, if declared event size
+    /// matches its serialization and if registry prefix of inception event is
+    /// derived from its data.
+    pub fn check_well_formed(&self) -> Result<(), Error> {
+        let declared = self.event.get_serialization_info().size;
+        let actual = self.event.serialize()?.len();
+        if declared != actual {
+            return Err(Error::SizeMismatch { declared, actual });
+        }
+        match self.event {
+            Event::Management(ref man) => match man.event_type {
+                ManagerEventType::Vcp(ref vcp) if !man.has_derived_prefix(vcp)? => {
+                    Err(Error::DigestMismatch)
+                }
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        }
     }
 
     pub fn serialize(&self) -> Result<Vec<u8>, Error> {
-        Ok(match &self.event {
-            Event::Management(man) => {
-                [man.serialize()?, self.seal.serialize()?].join("-".as_bytes())
-            }
-            Event::Vc(vc) => [vc.serialize()?, self.seal.serialize()?].join("-".as_bytes()),
+        let event = match &self.event {
+            Event::Management(man) => man.serialize()?,
+            Event::Vc(vc) => vc.serialize()?,
+            Event::Unknown { raw, .. } => raw.clone(),
+        };
+        Ok(match self.seal {
+            Some(ref seal) => [event, seal.serialize()?].join("-".as_bytes()),
+            None => event,
         })
     }
 
     pub fn get_event(&self) -> Event {
         self.event.clone()
     }
+
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Returns attached source seal. `None` if event isn't anchored yet.
+    pub fn source_seal(&self) -> Option<&AttachedSourceSeal> {
+        self.seal.as_ref()
+    }
+}
+
+/// Builds event anchored in given KEL interaction event.
+pub fn verifiable_from_anchor(
+    tel_event: Event,
+    ixn: &SignedEventMessage,
+    derivation: &SelfAddressing,
+) -> Result<VerifiableEvent, Error> {
+    let seal = EventSourceSeal {
+        sn: ixn.event_message.event.sn,
+        digest: derivation.derive(&ixn.event_message.serialize()?),
+    };
+    Ok(VerifiableEvent::new(tel_event, seal.into()))
+}
+
+#[test]
+fn test_unanchored_serialization() -> Result<(), Error> {
+    use crate::tel::event_generator;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+
+    let unanchored = VerifiableEvent::unanchored(vcp.clone());
+    assert_eq!(unanchored.seal, None);
+    let serialized = unanchored.serialize()?;
+    assert_eq!(serialized, vcp.serialize()?);
+    assert!(!String::from_utf8(serialized).unwrap().contains("-GAB"));
+
+    let anchored = VerifiableEvent::new(
+        vcp,
+        AttachedSourceSeal::new(1, "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?),
+    );
+    assert!(String::from_utf8(anchored.serialize()?)
+        .unwrap()
+        .contains("-GAB"));
+
+    Ok(())
+}
+
+#[test]
+fn test_try_new() -> Result<(), Error> {
+    use crate::tel::event_generator;
+    use keri::prefix::SelfAddressingPrefix;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+
+    let default_seal = AttachedSourceSeal::new(1, SelfAddressingPrefix::default());
+    assert!(VerifiableEvent::try_new(vcp.clone(), default_seal.clone()).is_err());
+    assert!(!VerifiableEvent::new(vcp.clone(), default_seal).is_anchored());
+    assert!(!VerifiableEvent::unanchored(vcp.clone()).is_anchored());
+
+    let seal = AttachedSourceSeal::new(1, "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?);
+    assert_eq!(
+        VerifiableEvent::try_new(vcp.clone(), seal.clone())?,
+        VerifiableEvent::new(vcp.clone(), seal.clone())
+    );
+    assert!(VerifiableEvent::new(vcp, seal).is_anchored());
+
+    Ok(())
+}
+
+#[test]
+fn test_verifiable_from_anchor() -> Result<(), Error> {
+    use crate::tel::event_generator;
+    use keri::event_message::EventMessage;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+    let ixn_raw = r#"{"v":"KERI10JSON000098_","i":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"1","t":"ixn","p":"EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8","a":[]}"#;
+    let ixn_message: EventMessage = serde_json::from_str(ixn_raw)?;
+    let ixn = SignedEventMessage::new(&ixn_message, vec![]);
+
+    // Source seal built by hand from anchoring ixn.
+    let ixn_sn = ixn.event_message.event.sn;
+    let ixn_digest = SelfAddressing::Blake3_256.derive(&ixn.event_message.serialize()?);
+    let expected = VerifiableEvent::new(
+        vcp.clone(),
+        EventSourceSeal {
+            sn: ixn_sn,
+            digest: ixn_digest,
+        }
+        .into(),
+    );
+
+    assert_eq!(
+        verifiable_from_anchor(vcp, &ixn, &SelfAddressing::Blake3_256)?,
+        expected
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_accessors() -> Result<(), Error> {
+    use crate::tel::event_generator;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+    let seal = AttachedSourceSeal::new(1, "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?);
+
+    let verifiable = VerifiableEvent::new(vcp.clone(), seal.clone());
+    assert_eq!(verifiable.event(), &vcp);
+    assert_eq!(verifiable.source_seal(), Some(&seal));
+
+    let unanchored = VerifiableEvent::unanchored(vcp.clone());
+    assert_eq!(unanchored.event(), &vcp);
+    assert_eq!(unanchored.source_seal(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_well_formed() -> Result<(), Error> {
+    use crate::tel::event_generator;
+
+    let issuer_prefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let vcp = event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+    let seal = AttachedSourceSeal::new(1, "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?);
+    assert!(VerifiableEvent::new(vcp.clone(), seal.clone())
+        .check_well_formed()
+        .is_ok());
+
+    let tampered = match vcp {
+        Event::Management(mut man) => {
+            man.serialization_info.size += 1;
+            Event::Management(man)
+        }
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    assert!(matches!(
+        VerifiableEvent::new(tampered, seal).check_well_formed(),
+        Err(Error::SizeMismatch { .. })
+    ));
+
+    Ok(())
 }
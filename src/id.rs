@@ -0,0 +1,81 @@
+use keri::prefix::{IdentifierPrefix, SelfAddressingPrefix};
+use serde::{Deserialize, Serialize};
+
+/// Identifier of registry, so it can't be mistaken for vc identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RegistryId(pub IdentifierPrefix);
+
+impl RegistryId {
+    pub fn prefix(&self) -> &IdentifierPrefix {
+        &self.0
+    }
+}
+
+impl From<IdentifierPrefix> for RegistryId {
+    fn from(prefix: IdentifierPrefix) -> Self {
+        RegistryId(prefix)
+    }
+}
+
+impl From<RegistryId> for IdentifierPrefix {
+    fn from(id: RegistryId) -> Self {
+        id.0
+    }
+}
+
+/// Identifier of vc, digest of its content.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CredentialId(pub SelfAddressingPrefix);
+
+impl CredentialId {
+    pub fn digest(&self) -> &SelfAddressingPrefix {
+        &self.0
+    }
+
+    /// Returns identifier under which vc events are stored.
+    pub fn prefix(&self) -> IdentifierPrefix {
+        IdentifierPrefix::SelfAddressing(self.0.clone())
+    }
+}
+
+impl From<SelfAddressingPrefix> for CredentialId {
+    fn from(digest: SelfAddressingPrefix) -> Self {
+        CredentialId(digest)
+    }
+}
+
+impl From<CredentialId> for SelfAddressingPrefix {
+    fn from(id: CredentialId) -> Self {
+        id.0
+    }
+}
+
+impl From<CredentialId> for IdentifierPrefix {
+    fn from(id: CredentialId) -> Self {
+        IdentifierPrefix::SelfAddressing(id.0)
+    }
+}
+
+#[test]
+fn test_id_conversions() -> Result<(), crate::error::Error> {
+    let registry_prefix: IdentifierPrefix =
+        "EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs".parse()?;
+    let registry_id = RegistryId::from(registry_prefix.clone());
+    assert_eq!(registry_id.prefix(), &registry_prefix);
+    assert_eq!(IdentifierPrefix::from(registry_id), registry_prefix);
+
+    let vc_digest: SelfAddressingPrefix = "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?;
+    let credential_id = CredentialId::from(vc_digest.clone());
+    assert_eq!(credential_id.digest(), &vc_digest);
+    assert_eq!(
+        credential_id.prefix(),
+        IdentifierPrefix::SelfAddressing(vc_digest.clone())
+    );
+    assert_eq!(
+        IdentifierPrefix::from(credential_id.clone()),
+        credential_id.prefix()
+    );
+    assert_eq!(SelfAddressingPrefix::from(credential_id), vc_digest);
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
 pub mod database;
 pub mod error;
 pub mod event;
+pub mod id;
 pub mod processor;
 pub mod seal;
 pub mod state;
 pub mod tel;
+#[cfg(test)]
+mod test_utils;
+#[cfg(any(test, feature = "test-vectors"))]
+pub mod test_vectors;
+pub mod verifier;
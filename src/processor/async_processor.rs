@@ -0,0 +1,69 @@
+use futures::{Stream, StreamExt};
+
+use crate::{
+    error::Error,
+    event::{
+        serializer::{DefaultSerializer, TelSerializer},
+        verifiable_event::VerifiableEvent,
+    },
+    processor::EventProcessor,
+    state::State,
+};
+
+/// Processes events pushed from asynchronous sources, like network sockets.
+pub struct AsyncEventProcessor<'d, S: TelSerializer = DefaultSerializer> {
+    processor: EventProcessor<'d, S>,
+}
+
+impl<'d, S: TelSerializer> AsyncEventProcessor<'d, S> {
+    pub fn new(processor: EventProcessor<'d, S>) -> Self {
+        Self { processor }
+    }
+
+    pub fn processor(&self) -> &EventProcessor<'d, S> {
+        &self.processor
+    }
+
+    /// Processes events in the order they arrive. Returned stream yields
+    /// result of processing for each event.
+    pub fn process_stream<'a>(
+        &'a self,
+        stream: impl Stream<Item = VerifiableEvent> + 'a,
+    ) -> impl Stream<Item = Result<State, Error>> + 'a {
+        stream.map(move |event| self.processor.process(event))
+    }
+}
+
+#[test]
+fn test_process_stream() -> Result<(), Error> {
+    use crate::{
+        state::vc_state::TelState,
+        tel::event_generator,
+        test_utils::{dummy_source_seal, make_inception, test_db},
+    };
+    use futures::{executor::block_on, stream};
+    use keri::derivation::self_addressing::SelfAddressing;
+
+    let (_root, db) = test_db();
+    let processor = AsyncEventProcessor::new(EventProcessor::new(&db));
+    let dummy_source_seal = dummy_source_seal()?;
+
+    let (vcp, state) = make_inception("EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some message".as_bytes());
+    let iss = event_generator::make_issuance_event(&state, vc_hash, None, None)?;
+    let events = vec![
+        VerifiableEvent::new(vcp, dummy_source_seal.clone().into()),
+        VerifiableEvent::new(iss.clone(), dummy_source_seal.clone().into()),
+        // The same vc can't be issued twice.
+        VerifiableEvent::new(iss, dummy_source_seal.into()),
+    ];
+
+    let outcomes: Vec<Result<State, Error>> =
+        block_on(processor.process_stream(stream::iter(events)).collect());
+    assert_eq!(outcomes.len(), 3);
+    assert!(matches!(outcomes[0], Ok(State::Management(_))));
+    assert!(matches!(outcomes[1], Ok(State::Tel(TelState::Issued(_)))));
+    assert!(outcomes[2].is_err());
+
+    Ok(())
+}
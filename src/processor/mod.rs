@@ -1,33 +1,279 @@
-use keri::prefix::{IdentifierPrefix, SelfAddressingPrefix};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use keri::{
+    derivation::self_addressing::SelfAddressing,
+    event_message::EventMessage,
+    prefix::{IdentifierPrefix, Prefix, SelfAddressingPrefix},
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     database::EventDatabase,
     error::Error,
-    event::{verifiable_event::VerifiableEvent, Event},
-    state::{vc_state::TelState, ManagerTelState, State},
+    event::{
+        manager_event::{ManagerEventType, ManagerTelEvent},
+        serializer::{DefaultSerializer, TelSerializer},
+        vc_event::{VCEvent, VCEventType},
+        verifiable_event::VerifiableEvent,
+        Event,
+    },
+    id::{CredentialId, RegistryId},
+    seal::{find_anchor, verify_anchor, EventSourceSeal},
+    state::{
+        vc_state::{fold_vc_events, TelState, Transition},
+        BackerPolicy, ManagerTelState, State,
+    },
+    tel::VcIdScheme,
 };
 
-pub struct EventProcessor<'d> {
+#[cfg(feature = "async")]
+pub mod async_processor;
+
+// All management and vc tels stored in database.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    management: Vec<(IdentifierPrefix, Vec<VerifiableEvent>)>,
+    tels: Vec<(IdentifierPrefix, Vec<VerifiableEvent>)>,
+}
+
+/// Summary of merging events of other database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MergeReport {
+    // Number of events copied from other database.
+    pub merged: usize,
+    // Number of events already present in database.
+    pub duplicates: usize,
+    // Prefixes and sns of events which differ from events stored in
+    // database. Following events of conflicting tel aren't merged.
+    pub conflicts: Vec<(IdentifierPrefix, u64)>,
+}
+
+// Major version of KERI protocol supported by processor.
+const SUPPORTED_MAJOR_VERSION: u8 = 1;
+
+// Maximal distance between sn of management event kept in escrow and sn of
+// registry event expected next.
+const MAX_ESCROW_DISTANCE: u64 = 16;
+
+// Maximal number of management events of the same sn kept in escrow for one
+// registry.
+const MAX_ESCROWED_CANDIDATES: usize = 4;
+
+pub struct EventProcessor<'d, S: TelSerializer = DefaultSerializer> {
     db: &'d EventDatabase,
+    serializer: S,
+    // States computed while processing, so new events can be applied without
+    // folding whole tel again. Each state is kept together with digest of
+    // tel tip it was computed from, so state is folded again when events
+    // were changed in database in other way than by processing.
+    management_states: Mutex<HashMap<IdentifierPrefix, (SelfAddressingPrefix, ManagerTelState)>>,
+    vc_states: Mutex<HashMap<IdentifierPrefix, (SelfAddressingPrefix, TelState)>>,
+    // Locks serializing processing of events of the same prefix, so
+    // concurrent events can't be applied to the same state.
+    prefix_locks: Mutex<HashMap<IdentifierPrefix, Arc<Mutex<()>>>>,
+    // Flush database after each processed event.
+    auto_flush: bool,
+    // Perform all verification checks while processing.
+    strict: bool,
+    // Whether events without source seal are accepted and stored as waiting
+    // for anchor.
+    accept_unanchored: bool,
+    // Write-ahead log of accepted events which may not be flushed to
+    // database yet.
+    wal: Option<Mutex<File>>,
+    // Identifiers which can't be registry issuers or backers.
+    blocklist: HashSet<IdentifierPrefix>,
+    // Scheme vc identifiers are computed with from vc content.
+    vc_id_scheme: VcIdScheme,
+}
+
+// Returns events of issuer KEL, indexed by their sn.
+fn kel_events(
+    kel: &keri::processor::EventProcessor,
+    issuer: &IdentifierPrefix,
+) -> Result<Vec<EventMessage>, Error> {
+    Ok((0..)
+        .map(|sn| kel.get_event_at_sn(issuer, sn))
+        .map_while(|event| event.transpose())
+        .map(|event| event.map(|event| event.signed_event_message.event_message))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<T>, Error> {
+    mutex
+        .lock()
+        .map_err(|_| Error::Generic("Poisoned lock".into()))
 }
+
 impl<'d> EventProcessor<'d> {
     pub fn new(db: &'d EventDatabase) -> Self {
-        Self { db }
+        Self::with_serializer(db, DefaultSerializer)
+    }
+
+    /// Restores tels dumped by `snapshot_to_path` into database, replacing
+    /// all its events, escrowed events and checkpoints. Events are stored as
+    /// they are, together with their source seals.
+    pub fn restore_from_path(db: &EventDatabase, path: &Path) -> Result<(), Error> {
+        let snapshot: Snapshot = serde_json::from_slice(&fs::read(path)?)?;
+        db.clear()?;
+        for (id, events) in snapshot.management {
+            db.set_management_events(events, &id)?;
+        }
+        for (id, events) in snapshot.tels {
+            db.set_events(events, &id)?;
+        }
+        Ok(())
+    }
+
+    /// Replays events from write-ahead log which didn't reach database
+    /// before crash, and clears the log. Events are replayed without strict
+    /// checks, because they were verified before they were logged. Returns
+    /// number of replayed events.
+    pub fn recover_from_wal(db: &EventDatabase, wal_path: &Path) -> Result<usize, Error> {
+        let processor = EventProcessor::new(db)
+            .strict(false)
+            .accept_unanchored(true);
+        let mut replayed = 0;
+        for line in BufReader::new(File::open(wal_path)?).lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let event: VerifiableEvent = serde_json::from_str(&line)?;
+            if !processor.is_stored(&event)? {
+                processor.process(event)?;
+                replayed += 1;
+            }
+        }
+        db.flush()?;
+        File::create(wal_path)?;
+        Ok(replayed)
+    }
+}
+
+impl<'d, S: TelSerializer> EventProcessor<'d, S> {
+    /// Creates processor which returns events encoded with given serializer.
+    /// Events are verified and stored in their version string encoding
+    /// regardless of it.
+    pub fn with_serializer(db: &'d EventDatabase, serializer: S) -> Self {
+        Self {
+            db,
+            serializer,
+            management_states: Mutex::new(HashMap::new()),
+            vc_states: Mutex::new(HashMap::new()),
+            prefix_locks: Mutex::new(HashMap::new()),
+            auto_flush: false,
+            strict: true,
+            accept_unanchored: false,
+            wal: None,
+            blocklist: HashSet::new(),
+            vc_id_scheme: VcIdScheme::default(),
+        }
+    }
+
+    /// Makes processor flush database after each successfully processed event.
+    pub fn with_auto_flush(mut self, auto_flush: bool) -> Self {
+        self.auto_flush = auto_flush;
+        self
+    }
+
+    /// Sets whether processor performs full verification of events. Strict
+    /// mode is the default. In permissive mode declared event size, source
+    /// seal digest algorithm, registry anchors and anchoring order aren't
+    /// checked, so it should be used only for events from trusted source.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether processor accepts events which aren't anchored in KEL
+    /// yet, like events processed locally before anchoring. Such events,
+    /// including ones with placeholder seal, are stored without seal until
+    /// it's attached with `update_source_seal`. Unanchored events are
+    /// rejected by default.
+    pub fn accept_unanchored(mut self, accept_unanchored: bool) -> Self {
+        self.accept_unanchored = accept_unanchored;
+        self
+    }
+
+    /// Makes processor reject management events of registries which issuer
+    /// is blocked, and events adding blocked backers.
+    pub fn with_blocklist(mut self, blocklist: HashSet<IdentifierPrefix>) -> Self {
+        self.blocklist = blocklist;
+        self
+    }
+
+    /// Sets scheme vc identifiers are computed with, so
+    /// `process_with_content` can check them against vc content. Digest of
+    /// vc content is expected by default.
+    pub fn with_vc_id_scheme(mut self, vc_id_scheme: VcIdScheme) -> Self {
+        self.vc_id_scheme = vc_id_scheme;
+        self
+    }
+
+    /// Makes processor append each accepted event to write-ahead log at
+    /// given path before storing it in database. Log is cleared when
+    /// database is flushed. Events lost in crash can be restored with
+    /// `recover_from_wal`.
+    pub fn with_wal(mut self, wal_path: &Path) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(wal_path)?;
+        self.wal = Some(Mutex::new(file));
+        Ok(self)
+    }
+
+    /// Writes all buffered database changes to disk.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.db.flush()?;
+        if let Some(ref wal) = self.wal {
+            lock(wal)?.set_len(0)?;
+        }
+        Ok(())
+    }
+
+    pub fn serializer(&self) -> &S {
+        &self.serializer
+    }
+
+    /// Parses event using processor's serializer.
+    pub fn parse_event(&self, data: &[u8]) -> Result<Event, Error> {
+        self.serializer.deserialize(data)
     }
 
     pub fn get_management_tel_state(
         &self,
-        id: &IdentifierPrefix,
+        registry_id: &RegistryId,
     ) -> Result<ManagerTelState, Error> {
+        self.fold_management_state(registry_id.prefix())
+    }
+
+    // Computes management state of registry from stored events, starting
+    // at its checkpoint if there is one.
+    fn fold_management_state(&self, id: &IdentifierPrefix) -> Result<ManagerTelState, Error> {
+        let (initial, skipped) = match self.valid_checkpoint(id)? {
+            Some(checkpoint) => {
+                let skipped = checkpoint.sn as usize + 1;
+                (checkpoint, skipped)
+            }
+            None => (ManagerTelState::default(), 0),
+        };
         match self.db.get_management_events(id) {
-            Some(events) => events.into_iter().fold(
-                Ok(ManagerTelState::default()),
+            Some(events) => events.skip(skipped).fold(
+                Ok(initial),
                 |state: Result<ManagerTelState, Error>,
                  ev: VerifiableEvent|
                  -> Result<ManagerTelState, Error> {
                     match ev.event {
                         Event::Management(event) => state?.apply(&event),
-                        Event::Vc(_) => Err(Error::Generic("Improper event type".into())),
+                        _ => Err(Error::Generic("Improper event type".into())),
                     }
                 },
             ),
@@ -35,45 +281,764 @@ impl<'d> EventProcessor<'d> {
         }
     }
 
-    pub fn get_vc_state(&self, vc_id: &IdentifierPrefix) -> Result<TelState, Error> {
-        match self.db.get_events(vc_id) {
-            Some(events) => events.into_iter().fold(
-                Ok(TelState::default()),
-                |state, ev| -> Result<TelState, Error> {
-                    match ev.event {
-                        Event::Vc(event) => state?.apply(&event),
-                        _ => state,
-                    }
-                },
-            ),
-            None => Ok(TelState::default()),
+    /// Stores current management state of registry as checkpoint, so
+    /// computing its state folds only events following the checkpoint.
+    pub fn checkpoint_management(&self, registry_id: &RegistryId) -> Result<(), Error> {
+        let state = self.get_management_tel_state(registry_id)?;
+        if state == ManagerTelState::default() {
+            return Err(Error::WrongState("Registry not incepted".into()));
+        }
+        self.db
+            .set_management_checkpoint(&state, registry_id.prefix())
+    }
+
+    // Returns management state checkpoint of registry, if it still matches
+    // stored event it was made at. Checkpoint made before event was
+    // rewritten is removed.
+    fn valid_checkpoint(&self, id: &IdentifierPrefix) -> Result<Option<ManagerTelState>, Error> {
+        let checkpoint = match self.db.get_management_checkpoint(id)? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        let checkpoint_event = self
+            .db
+            .get_management_events(id)
+            .and_then(|mut events| events.nth(checkpoint.sn as usize));
+        match checkpoint_event {
+            Some(event) if event.event.serialize()? == checkpoint.last => Ok(Some(checkpoint)),
+            _ => {
+                self.db.remove_management_checkpoint(id)?;
+                Ok(None)
+            }
         }
     }
 
+    /// Returns management events of registry, each paired with state
+    /// computed after applying it.
+    pub fn management_history(
+        &self,
+        registry_id: &RegistryId,
+    ) -> Result<Vec<(ManagerTelEvent, ManagerTelState)>, Error> {
+        let mut state = ManagerTelState::default();
+        let mut history = vec![];
+        for ev in self
+            .db
+            .get_management_events(registry_id.prefix())
+            .into_iter()
+            .flatten()
+        {
+            match ev.event {
+                Event::Management(event) => {
+                    state = state.apply(&event)?;
+                    history.push((event, state.clone()));
+                }
+                _ => return Err(Error::Generic("Improper event type".into())),
+            }
+        }
+        Ok(history)
+    }
+
+    /// Returns number of backer receipts required by registry as of its
+    /// management event of given sn.
+    pub fn threshold_at_sn(&self, registry_id: &RegistryId, sn: u64) -> Result<u64, Error> {
+        let state = self
+            .management_history(registry_id)?
+            .into_iter()
+            .take_while(|(event, _)| event.sn <= sn)
+            .last()
+            .map(|(_, state)| state)
+            .ok_or_else(|| Error::WrongState("Registry not incepted".into()))?;
+        if state.sn != sn {
+            return Err(Error::Generic(format!("No management event at sn {}", sn)));
+        }
+        Ok(match state.backer_policy {
+            BackerPolicy::Count(threshold) => threshold,
+            BackerPolicy::Subset(ref required) => required.len() as u64,
+        })
+    }
+
+    /// Returns backers added and removed by each registry rotation, as
+    /// `(sn, added, removed)` tuples ordered by sn.
+    pub fn backer_changelog(
+        &self,
+        registry_id: &RegistryId,
+    ) -> Result<Vec<(u64, Vec<IdentifierPrefix>, Vec<IdentifierPrefix>)>, Error> {
+        let history = self.management_history(registry_id)?;
+        Ok(history
+            .windows(2)
+            .map(|pair| {
+                let (_, ref before) = pair[0];
+                let (ref rotation, ref after) = pair[1];
+                let before = before.backers.clone().unwrap_or_default();
+                let after = after.backers.clone().unwrap_or_default();
+                let added = after
+                    .iter()
+                    .filter(|backer| !before.contains(backer))
+                    .cloned()
+                    .collect();
+                let removed = before
+                    .iter()
+                    .filter(|backer| !after.contains(backer))
+                    .cloned()
+                    .collect();
+                (rotation.sn, added, removed)
+            })
+            .collect())
+    }
+
+    pub fn get_vc_state(&self, vc_id: &CredentialId) -> Result<TelState, Error> {
+        self.fold_vc_state(&vc_id.prefix())
+    }
+
+    // Computes vc state from stored events.
+    fn fold_vc_state(&self, vc_id: &IdentifierPrefix) -> Result<TelState, Error> {
+        let events: Vec<VCEvent> = self
+            .db
+            .get_events(vc_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|ev| match ev.event {
+                Event::Vc(event) => Some(event.event),
+                _ => None,
+            })
+            .collect();
+        fold_vc_events(&events)
+    }
+
+    /// Returns vc state computed only from events anchored in KEL. Events
+    /// following the first unanchored one are skipped too.
+    pub fn anchored_vc_state(&self, vc_id: &CredentialId) -> Result<TelState, Error> {
+        let events: Vec<VCEvent> = self
+            .db
+            .get_events(&vc_id.prefix())
+            .into_iter()
+            .flatten()
+            .take_while(|ev| ev.is_anchored())
+            .filter_map(|ev| match ev.event {
+                Event::Vc(event) => Some(event.event),
+                _ => None,
+            })
+            .collect();
+        fold_vc_events(&events)
+    }
+
+    /// Returns vc state as of its event of given sn. Vc which has no events
+    /// is not issued at any sn.
+    pub fn vc_state_at_sn(&self, vc_id: &CredentialId, sn: u64) -> Result<TelState, Error> {
+        let events: Vec<VCEvent> = match self.db.get_events(&vc_id.prefix()) {
+            Some(events) => events
+                .filter_map(|ev| match ev.event {
+                    Event::Vc(event) => Some(event.event),
+                    _ => None,
+                })
+                .collect(),
+            None => return Ok(TelState::default()),
+        };
+        match events.last() {
+            Some(tip) if tip.sn < sn => Err(Error::OutOfOrder {
+                expected: tip.sn,
+                got: sn,
+            }),
+            _ => {
+                let up_to_sn = events.iter().take_while(|event| event.sn <= sn).count();
+                fold_vc_events(&events[..up_to_sn])
+            }
+        }
+    }
+
+    /// Returns vc state together with state transitions made by its events,
+    /// in order they were applied.
+    pub fn get_vc_state_with_history(
+        &self,
+        vc_id: &CredentialId,
+    ) -> Result<(TelState, Vec<Transition>), Error> {
+        let mut state = TelState::default();
+        let mut history = vec![];
+        for event in self.db.get_events(&vc_id.prefix()).into_iter().flatten() {
+            let vc_ev = match event.event {
+                Event::Vc(ref vc) => &vc.event,
+                _ => continue,
+            };
+            // Revoked state is terminal, following events are ignored.
+            if state == TelState::Revoked {
+                break;
+            }
+            let new_state = state.apply(vc_ev)?;
+            history.push(Transition {
+                from: state.kind(),
+                to: new_state.kind(),
+                event_digest: self.chain_digest(&event.event)?,
+            });
+            state = new_state;
+        }
+        Ok((state, history))
+    }
+
+    /// Verifies stored vc events one by one, yielding digest of each event,
+    /// as bound by `p` of the following event, with vc state after applying
+    /// it. Iteration stops after the first event which can't be applied.
+    pub fn verify_stream(
+        &self,
+        vc_id: &CredentialId,
+    ) -> impl Iterator<Item = Result<(SelfAddressingPrefix, TelState), Error>> + '_ {
+        let mut state = Some(TelState::default());
+        self.db
+            .get_events(&vc_id.prefix())
+            .into_iter()
+            .flatten()
+            .map_while(move |event| {
+                let current = state.take()?;
+                let result = match event.event {
+                    Event::Vc(ref vc) => current.apply(&vc.event),
+                    _ => Err(Error::Generic("Improper event type".into())),
+                }
+                .and_then(|new_state| Ok((self.chain_digest(&event.event)?, new_state)));
+                if let Ok((_, ref new_state)) = result {
+                    state = Some(new_state.clone());
+                }
+                Some(result)
+            })
+    }
+
     // Process verifiable event. It doesn't check if source seal is correct. Just add event to tel.
     pub fn process(&self, event: VerifiableEvent) -> Result<State, Error> {
+        let state = self.process_event(event)?;
+        // Accepted management event may be the one escrowed events wait for.
+        // Event is already stored, so failure of draining escrow doesn't fail
+        // its processing. Escrowed events which failed transiently are kept
+        // for the next drain.
+        if let State::Management(ref man) = state {
+            let _ = self.process_escrow(&man.prefix);
+        }
+        Ok(state)
+    }
+
+    /// Processes escrowed management events of registry which follow its
+    /// tip, for example to retry them after transient failure.
+    pub fn process_escrowed(&self, registry_id: &RegistryId) -> Result<(), Error> {
+        self.process_escrow(registry_id.prefix())
+    }
+
+    fn process_event(&self, event: VerifiableEvent) -> Result<State, Error> {
+        let serialization_info = event.event.get_serialization_info();
+        if serialization_info.major_version != SUPPORTED_MAJOR_VERSION {
+            return Err(Error::UnsupportedVersion {
+                major: serialization_info.major_version,
+                minor: serialization_info.minor_version,
+            });
+        }
+        if self.strict {
+            let actual_size = event.event.serialize()?.len();
+            if serialization_info.size != actual_size {
+                return Err(Error::SizeMismatch {
+                    declared: serialization_info.size,
+                    actual: actual_size,
+                });
+            }
+            // Placeholder seals are handled by unanchored events policy.
+            match event.seal {
+                Some(ref seal) if !seal.seal.is_placeholder() => seal.seal.validate()?,
+                _ => (),
+            }
+        }
+        let event = if event.is_anchored() {
+            event
+        } else if self.accept_unanchored {
+            VerifiableEvent::unanchored(event.event)
+        } else {
+            return Err(Error::MissingAnchor);
+        };
+        let prefix_lock = self.prefix_lock(&event.event.get_prefix())?;
+        let _prefix_guard = lock(&prefix_lock)?;
         match &event.event.clone() {
-            Event::Management(ref man) => self
-                .get_management_tel_state(&man.prefix)?
-                .apply(man)
-                .map(|state| {
-                    self.db
-                        .add_new_management_event(event, &man.prefix)
-                        .unwrap();
-                    State::Management(state)
-                }),
-            Event::Vc(ref vc_ev) => self.get_vc_state(&vc_ev.prefix)?.apply(vc_ev).map(|state| {
-                self.db.add_new_event(event, &vc_ev.prefix).unwrap();
-                State::Tel(state)
-            }),
+            Event::Management(ref man) => {
+                self.validate_not_blocked(man)?;
+                let applied = self
+                    .validate_management_sn(man)
+                    .and_then(|_| self.current_management_state(&man.prefix)?.apply(man));
+                match applied {
+                    Ok(state) => {
+                        self.append_to_wal(&event)?;
+                        self.db.add_new_management_event(event, &man.prefix)?;
+                        self.cache_management_state(&man.prefix, &state)?;
+                        if self.auto_flush {
+                            self.flush()?;
+                        }
+                        Ok(State::Management(state))
+                    }
+                    // Rotation applied on top of the tip can't replay stored
+                    // rotation, because its `p` binds the tip. So stored
+                    // rotations are searched only for events which can't be
+                    // applied.
+                    Err(e) => {
+                        self.validate_not_replayed(man)?;
+                        // Event from the future. Keep it in escrow instead of dropping it.
+                        if let Error::OutOfOrder { .. } = e {
+                            self.escrow_management_event(event, &man.prefix)?;
+                        }
+                        Err(e)
+                    }
+                }
+            }
+            Event::Vc(ref timestamped) => {
+                let vc_ev = &timestamped.event;
+                self.validate_registry(vc_ev)?;
+                self.validate_issuance_backers(vc_ev)?;
+                if self.strict {
+                    self.validate_registry_anchor(vc_ev)?;
+                    self.validate_anchoring_order(&event)?;
+                }
+                let state = self.current_vc_state(&vc_ev.prefix)?.apply(vc_ev)?;
+                self.append_to_wal(&event)?;
+                self.db.add_new_event(event, &vc_ev.prefix)?;
+                if let Some(tip) = self.db.get_tip(&vc_ev.prefix)? {
+                    lock(&self.vc_states)?
+                        .insert(vc_ev.prefix.clone(), (tip.digest, state.clone()));
+                }
+                if self.auto_flush {
+                    self.flush()?;
+                }
+                Ok(State::Tel(state))
+            }
+            // Unknown events can't be applied to state. They are only stored,
+            // so they can be forwarded.
+            Event::Unknown { .. } => {
+                let prefix = event.event.get_prefix();
+                if prefix == IdentifierPrefix::default() {
+                    return Err(Error::Generic("Unknown event without identifier".into()));
+                }
+                if !self.is_stored(&event)? {
+                    self.append_to_wal(&event)?;
+                    self.db.add_unknown_event(event, &prefix)?;
+                    if self.auto_flush {
+                        self.flush()?;
+                    }
+                }
+                Ok(State::Unknown)
+            }
         }
     }
 
-    pub fn get_management_events(&self, id: &IdentifierPrefix) -> Result<Option<Vec<u8>>, Error> {
-        match self.db.get_management_events(id) {
+    /// Processes event, checking first if its source seal points to event
+    /// of issuer KEL which seals it. Issuer of registry inception is taken
+    /// from the event, and issuer of other events from registry state.
+    pub fn process_verified(
+        &self,
+        event: VerifiableEvent,
+        kel: &keri::processor::EventProcessor,
+    ) -> Result<State, Error> {
+        let issuer = self.issuer_of_event(&event.event)?;
+        self.process_anchored(event, &kel_events(kel, &issuer)?)
+    }
+
+    // Processes event if it's anchored in given issuer KEL events.
+    fn process_anchored(
+        &self,
+        event: VerifiableEvent,
+        kel_events: &[EventMessage],
+    ) -> Result<State, Error> {
+        verify_anchor(&event, kel_events)?;
+        self.process(event)
+    }
+
+    // Returns identifier of issuer, which KEL anchors given event.
+    fn issuer_of_event(&self, event: &Event) -> Result<IdentifierPrefix, Error> {
+        let registry_id = match event {
+            Event::Management(ManagerTelEvent {
+                event_type: ManagerEventType::Vcp(ref vcp),
+                ..
+            }) => return Ok(vcp.issuer_id.clone()),
+            Event::Management(ref man) => man.prefix.clone(),
+            Event::Vc(ref vc) => vc
+                .event
+                .registry_id()
+                .or_else(|| self.registry_of_vc(&vc.event.prefix))
+                .ok_or_else(|| Error::Generic("Unknown registry of vc".into()))?,
+            Event::Unknown { .. } => return Err(Error::Generic("Improper event type".into())),
+        };
+        if self.stored_management_count(&registry_id)? == 0 {
+            return Err(Error::Generic(format!(
+                "Unknown registry {}",
+                registry_id.to_str()
+            )));
+        }
+        Ok(self.current_management_state(&registry_id)?.issuer)
+    }
+
+    /// Processes vc event, checking first if its prefix is identifier of
+    /// given credential content, computed with processor's vc id scheme.
+    pub fn process_with_content(
+        &self,
+        event: VerifiableEvent,
+        content: &[u8],
+    ) -> Result<State, Error> {
+        match event.event {
+            Event::Vc(ref vc_ev) => {
+                let registry_id = vc_ev
+                    .event
+                    .registry_id()
+                    .or_else(|| self.registry_of_vc(&vc_ev.event.prefix));
+                match (&vc_ev.event.prefix, registry_id) {
+                    (IdentifierPrefix::SelfAddressing(ref vc_digest), Some(ref registry_id))
+                        if self.vc_id_scheme.verify(vc_digest, registry_id, content) =>
+                    {
+                        self.process(event)
+                    }
+                    _ => Err(Error::Generic("Vc prefix doesn't match its content".into())),
+                }
+            }
+            _ => Err(Error::Generic("Improper event type".into())),
+        }
+    }
+
+    // Keeps management event which sn skips ahead of registry tip in escrow,
+    // until events preceding it arrive. Its binding to previous event can't
+    // be checked yet, so several candidates of the same sn are kept, and
+    // the ones which can't be applied are dropped once their sn comes. Escrow
+    // of registry is bounded: event too far ahead of the tip, or exceeding
+    // number of candidates of its sn, is dropped, and event already escrowed
+    // isn't kept twice.
+    fn escrow_management_event(
+        &self,
+        event: VerifiableEvent,
+        id: &IdentifierPrefix,
+    ) -> Result<(), Error> {
+        let sn = event.event.get_sn();
+        if sn.saturating_sub(self.stored_management_count(id)?) > MAX_ESCROW_DISTANCE {
+            return Ok(());
+        }
+        let escrowed = self.escrowed_management_events(id);
+        let candidates = escrowed
+            .iter()
+            .filter(|escrowed| escrowed.event.get_sn() == sn)
+            .count();
+        if candidates < MAX_ESCROWED_CANDIDATES
+            && !escrowed
+                .iter()
+                .any(|escrowed| escrowed.event == event.event)
+        {
+            self.db.add_escrowed_management_event(event, id)?;
+        }
+        Ok(())
+    }
+
+    // Processes escrowed management events which follow the current tip of
+    // registry, until escrow has none. Candidates of the next sn are tried
+    // in order they were escrowed, and the ones which can't be applied are
+    // dropped. Candidate which failed with retryable error is kept in
+    // escrow, together with the ones following it. Escrowed events which
+    // can't follow the tip anymore are dropped.
+    fn process_escrow(&self, id: &IdentifierPrefix) -> Result<(), Error> {
+        loop {
+            let escrowed = self.escrowed_management_events(id);
+            if escrowed.is_empty() {
+                return Ok(());
+            }
+            let expected = self.stored_management_count(id)?;
+            let (next, waiting): (Vec<_>, Vec<_>) = escrowed
+                .into_iter()
+                .filter(|event| event.event.get_sn() >= expected)
+                .partition(|event| event.event.get_sn() == expected);
+            self.db.set_escrowed_management_events(waiting, id)?;
+            let mut applied = false;
+            let mut next = next.into_iter();
+            while let Some(event) = next.next() {
+                match self.process_event(event.clone()) {
+                    Ok(_) => {
+                        applied = true;
+                        break;
+                    }
+                    Err(e) if e.is_retryable() => {
+                        for event in std::iter::once(event).chain(next) {
+                            self.db.add_escrowed_management_event(event, id)?;
+                        }
+                        return Err(e);
+                    }
+                    Err(_) => (),
+                }
+            }
+            if !applied {
+                return Ok(());
+            }
+        }
+    }
+
+    // Appends event to write-ahead log, if processor keeps one.
+    fn append_to_wal(&self, event: &VerifiableEvent) -> Result<(), Error> {
+        if let Some(ref wal) = self.wal {
+            let mut file = lock(wal)?;
+            let mut line = serde_json::to_vec(event)?;
+            line.push(b'\n');
+            file.write_all(&line)?;
+            file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    // Checks if the same event is already stored in database.
+    fn is_stored(&self, event: &VerifiableEvent) -> Result<bool, Error> {
+        Ok(match event.event {
+            Event::Management(ref man) => {
+                self.management_event_at_sn(&man.prefix, man.sn).is_some()
+            }
+            Event::Vc(ref vc) => self
+                .db
+                .get_events(&vc.event.prefix)
+                .map(|mut events| events.any(|stored| stored.event == event.event))
+                .unwrap_or(false),
+            Event::Unknown { .. } => self
+                .db
+                .get_unknown_events(&event.event.get_prefix())
+                .map(|mut events| events.any(|stored| stored.event == event.event))
+                .unwrap_or(false),
+        })
+    }
+
+    // Returns lock guarding processing of events of given prefix.
+    fn prefix_lock(&self, id: &IdentifierPrefix) -> Result<Arc<Mutex<()>>, Error> {
+        Ok(lock(&self.prefix_locks)?
+            .entry(id.clone())
+            .or_default()
+            .clone())
+    }
+
+    // Returns cached management state, or folds stored events if it isn't
+    // cached or stored events changed since it was cached.
+    fn current_management_state(&self, id: &IdentifierPrefix) -> Result<ManagerTelState, Error> {
+        let tip = self.db.get_management_tip(id)?;
+        let cached = lock(&self.management_states)?.get(id).cloned();
+        match (tip, cached) {
+            (Some(tip), Some((digest, state))) if digest == tip.digest => Ok(state),
+            _ => self.fold_management_state(id),
+        }
+    }
+
+    // Caches management state computed from currently stored events.
+    fn cache_management_state(
+        &self,
+        id: &IdentifierPrefix,
+        state: &ManagerTelState,
+    ) -> Result<(), Error> {
+        if let Some(tip) = self.db.get_management_tip(id)? {
+            lock(&self.management_states)?.insert(id.clone(), (tip.digest, state.clone()));
+        }
+        Ok(())
+    }
+
+    // Returns cached vc state, or folds stored events if it isn't cached or
+    // stored events changed since it was cached.
+    fn current_vc_state(&self, vc_id: &IdentifierPrefix) -> Result<TelState, Error> {
+        let tip = self.db.get_tip(vc_id)?;
+        let cached = lock(&self.vc_states)?.get(vc_id).cloned();
+        match (tip, cached) {
+            (Some(tip), Some((digest, state))) if digest == tip.digest => Ok(state),
+            _ => self.fold_vc_state(vc_id),
+        }
+    }
+
+    // Check if management event isn't made by blocked issuer and doesn't add
+    // blocked backer.
+    fn validate_not_blocked(&self, event: &ManagerTelEvent) -> Result<(), Error> {
+        if self.blocklist.is_empty() {
+            return Ok(());
+        }
+        let (issuer, backers) = match event.event_type {
+            ManagerEventType::Vcp(ref vcp) => (vcp.issuer_id.clone(), &vcp.backers),
+            ManagerEventType::Vrt(ref vrt) => (
+                self.current_management_state(&event.prefix)?.issuer,
+                &vrt.backers_to_add,
+            ),
+        };
+        match std::iter::once(&issuer)
+            .chain(backers.iter())
+            .find(|id| self.blocklist.contains(id))
+        {
+            Some(blocked) => Err(Error::Blocked(blocked.clone())),
+            None => Ok(()),
+        }
+    }
+
+    // Check if declared sn of management event is the next sn of registry
+    // events stored in database.
+    fn validate_management_sn(&self, event: &ManagerTelEvent) -> Result<(), Error> {
+        let expected = self.stored_management_count(&event.prefix)?;
+        if event.sn > expected {
+            Err(Error::OutOfOrder {
+                expected,
+                got: event.sn,
+            })
+        } else if event.sn < expected {
+            Err(Error::WrongState(format!(
+                "Event sn {} repeats stored event, expected sn {}",
+                event.sn, expected
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Check if rotation isn't body of already stored rotation, resubmitted
+    // with other sn.
+    fn validate_not_replayed(&self, event: &ManagerTelEvent) -> Result<(), Error> {
+        if let ManagerEventType::Vcp(_) = event.event_type {
+            return Ok(());
+        }
+        let replayed = self
+            .db
+            .get_management_events(&event.prefix)
+            .into_iter()
+            .flatten()
+            .any(|stored| match stored.event {
+                Event::Management(ref man) => {
+                    man.sn != event.sn && man.event_type == event.event_type
+                }
+                _ => false,
+            });
+        if replayed {
+            Err(Error::WrongState(format!(
+                "Rotation at sn {} replays already stored rotation",
+                event.sn
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Check if vc event refers to the same registry as vc issuance.
+    fn validate_registry(&self, event: &VCEvent) -> Result<(), Error> {
+        // The same vc prefix can't be issued again or revoked by other
+        // registry.
+        match (self.registry_of_vc(&event.prefix), event.registry_id()) {
+            (Some(expected), Some(got)) if expected != got => Err(Error::WrongState(format!(
+                "Vc was issued in registry {}, got event from registry {}",
+                expected.to_str(),
+                got.to_str()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // Check if vc isn't issued in registry which uses backers, but has none
+    // to witness issuance yet.
+    fn validate_issuance_backers(&self, event: &VCEvent) -> Result<(), Error> {
+        let registry_id = match (&event.event_type, event.registry_id()) {
+            (VCEventType::Iss(_) | VCEventType::Bis(_), Some(registry_id)) => registry_id,
+            _ => return Ok(()),
+        };
+        match self.current_management_state(&registry_id)?.backers {
+            Some(ref backers) if backers.is_empty() => Err(Error::WrongState(format!(
+                "Registry {} has no backers, add backer by rotation before issuance",
+                registry_id.to_str()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // Check if vc event isn't anchored in KEL before previous event of the same vc.
+    fn validate_anchoring_order(&self, event: &VerifiableEvent) -> Result<(), Error> {
+        let last_event = self
+            .db
+            .get_tip(&event.event.get_prefix())?
+            .map(|tip| tip.last);
+        match (last_event.and_then(|last| last.seal), &event.seal) {
+            (Some(last_seal), Some(seal)) if seal.seal.sn < last_seal.seal.sn => Err(
+                Error::Generic("Event anchored in KEL before previous event".into()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    // Check if registry anchor of vc event points to management event stored in db.
+    fn validate_registry_anchor(&self, event: &VCEvent) -> Result<(), Error> {
+        let registry_anchor = match event.event_type {
+            VCEventType::Bis(ref iss) => Some(&iss.registry_anchor),
+            VCEventType::Brv(ref rev) => rev.registry_anchor.as_ref(),
+            _ => None,
+        };
+        match registry_anchor {
+            Some(anchor) => {
+                let management_event = self
+                    .management_event_at_sn(&anchor.prefix, anchor.sn)
+                    .ok_or_else(|| Error::Generic("Unknown registry anchor".into()))?;
+                if let Some(expected) = self.management_digest_algorithm(&anchor.prefix)? {
+                    if anchor.event_digest.derivation != expected {
+                        return Err(Error::DigestAlgorithmMismatch {
+                            expected,
+                            got: anchor.event_digest.derivation.clone(),
+                        });
+                    }
+                }
+                if anchor
+                    .event_digest
+                    .verify_binding(&management_event.event.chained_bytes()?)
+                {
+                    Ok(())
+                } else {
+                    Err(Error::Generic("Registry anchor doesn't match".into()))
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Returns digest algorithm used by the latest stored management event:
+    // algorithm of previous event digest for rotation, and of registry prefix
+    // for inception.
+    fn management_digest_algorithm(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<SelfAddressing>, Error> {
+        let tip = self.db.get_management_tip(id)?;
+        Ok(match tip.map(|tip| tip.last.event) {
+            Some(Event::Management(ManagerTelEvent {
+                event_type: ManagerEventType::Vrt(ref vrt),
+                ..
+            })) => Some(vrt.prev_event.derivation.clone()),
+            Some(Event::Management(ManagerTelEvent {
+                prefix: IdentifierPrefix::SelfAddressing(ref prefix),
+                ..
+            })) => Some(prefix.derivation.clone()),
+            _ => None,
+        })
+    }
+
+    // Returns digest of event as bound by `p` of the following event: made
+    // over event bytes without `dt`, with digest algorithm of registry event
+    // belongs to.
+    fn chain_digest(&self, event: &Event) -> Result<SelfAddressingPrefix, Error> {
+        let registry_id = match event {
+            Event::Vc(_) => self.registry_of_vc(&event.get_prefix()),
+            _ => Some(event.get_prefix()),
+        };
+        let derivation = match registry_id {
+            Some(ref registry_id) => self.management_digest_algorithm(registry_id)?,
+            None => None,
+        };
+        Ok(derivation
+            .unwrap_or(SelfAddressing::Blake3_256)
+            .derive(&event.chained_bytes()?))
+    }
+
+    // Returns identifier of registry vc was issued in, if its issuance is
+    // stored.
+    fn registry_of_vc(&self, vc_id: &IdentifierPrefix) -> Option<IdentifierPrefix> {
+        self.db
+            .get_events(vc_id)
+            .and_then(|mut events| events.next())
+            .and_then(|issuance| match issuance.event {
+                Event::Vc(ref vc) => vc.event.registry_id(),
+                _ => None,
+            })
+    }
+
+    pub fn get_management_events(
+        &self,
+        registry_id: &RegistryId,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        match self.db.get_management_events(registry_id.prefix()) {
             Some(events) => Ok(Some(
                 events
-                    .map(|event| event.serialize().unwrap_or_default())
+                    .map(|event| self.serialize_verifiable_event(&event).unwrap_or_default())
                     .fold(vec![], |mut accum, serialized_event| {
                         accum.extend(serialized_event);
                         accum
@@ -83,133 +1048,541 @@ impl<'d> EventProcessor<'d> {
         }
     }
 
-    pub fn get_events(&self, vc_id: &SelfAddressingPrefix) -> Result<Vec<VerifiableEvent>, Error> {
-        let prefix = IdentifierPrefix::SelfAddressing(vc_id.to_owned());
-        match self.db.get_events(&prefix) {
-            Some(events) => Ok(events.collect()),
-            None => Ok(vec![]),
+    fn serialize_verifiable_event(&self, event: &VerifiableEvent) -> Result<Vec<u8>, Error> {
+        let serialized_event = self.serializer.serialize(&event.event)?;
+        Ok(match event.seal {
+            Some(ref seal) => [serialized_event, seal.serialize()?].join("-".as_bytes()),
+            None => serialized_event,
+        })
+    }
+
+    /// Returns identifiers of all vcs issued in given registry.
+    pub fn get_registry_vcs(&self, registry_id: &RegistryId) -> Result<Vec<CredentialId>, Error> {
+        Ok(self
+            .db
+            .get_registry_vcs(registry_id.prefix())?
+            .into_iter()
+            .filter_map(|id| match id {
+                IdentifierPrefix::SelfAddressing(vc_digest) => Some(CredentialId::from(vc_digest)),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Returns source seals of all management and vc events of registry,
+    /// ordered by sn of anchoring KEL event. Seal anchoring many events is
+    /// returned once.
+    pub fn registry_anchor_seals(
+        &self,
+        registry_id: &RegistryId,
+    ) -> Result<Vec<EventSourceSeal>, Error> {
+        let management_events = self
+            .db
+            .get_management_events(registry_id.prefix())
+            .into_iter()
+            .flatten();
+        let vc_events = self
+            .get_registry_vcs(registry_id)?
+            .into_iter()
+            .flat_map(|vc_id| self.db.get_events(&vc_id.prefix()).into_iter().flatten());
+        let mut seen = HashSet::new();
+        let mut seals: Vec<EventSourceSeal> = management_events
+            .chain(vc_events)
+            .filter_map(|event| event.seal)
+            .map(|seal| seal.seal)
+            .filter(|seal| seen.insert((seal.sn, seal.digest.to_str())))
+            .collect();
+        seals.sort_by_key(|seal| seal.sn);
+        Ok(seals)
+    }
+
+    /// Returns all management and vc events of registry anchored in KEL
+    /// events of sn between `from_kel_sn` and `to_kel_sn` inclusive, ordered
+    /// by sn of anchoring KEL event. Unanchored events are skipped.
+    pub fn events_in_kel_window(
+        &self,
+        registry_id: &RegistryId,
+        from_kel_sn: u64,
+        to_kel_sn: u64,
+    ) -> Result<Vec<VerifiableEvent>, Error> {
+        let management_events = self
+            .db
+            .get_management_events(registry_id.prefix())
+            .into_iter()
+            .flatten();
+        let vc_events = self
+            .get_registry_vcs(registry_id)?
+            .into_iter()
+            .flat_map(|vc_id| self.db.get_events(&vc_id.prefix()).into_iter().flatten());
+        let mut events: Vec<VerifiableEvent> = management_events
+            .chain(vc_events)
+            .filter(|event| match event.seal {
+                Some(ref seal) => (from_kel_sn..=to_kel_sn).contains(&seal.seal.sn),
+                None => false,
+            })
+            .collect();
+        // Stable sort keeps processing order of events anchored in the same
+        // KEL event.
+        events.sort_by_key(|event| event.seal.as_ref().map(|seal| seal.seal.sn));
+        Ok(events)
+    }
+
+    /// Scans KEL of issuer for event sealing given TEL event and returns
+    /// source seal pointing to it, for events received without one.
+    pub fn find_anchor_in_kel(
+        &self,
+        tel_event: &Event,
+        kel: &keri::processor::EventProcessor,
+        issuer: &IdentifierPrefix,
+    ) -> Result<Option<EventSourceSeal>, Error> {
+        find_anchor(tel_event, &kel_events(kel, issuer)?)
+    }
+
+    /// Verifies if source seal of each management and vc event of registry
+    /// points to event of issuer KEL which seals it. Returns digests of
+    /// events which anchors don't resolve, as bound by `p` of the following
+    /// event, together with reasons. Empty list means that registry is fully
+    /// verified.
+    pub fn verify_registry_anchoring(
+        &self,
+        registry_id: &RegistryId,
+        kel: &keri::processor::EventProcessor,
+    ) -> Result<Vec<(SelfAddressingPrefix, Error)>, Error> {
+        let issuer = self.current_management_state(registry_id.prefix())?.issuer;
+        self.registry_anchoring_failures(registry_id, &kel_events(kel, &issuer)?)
+    }
+
+    // Checks anchors of all registry events against given issuer KEL events.
+    fn registry_anchoring_failures(
+        &self,
+        registry_id: &RegistryId,
+        kel_events: &[EventMessage],
+    ) -> Result<Vec<(SelfAddressingPrefix, Error)>, Error> {
+        if self.stored_management_count(registry_id.prefix())? == 0 {
+            return Err(Error::WrongState("Registry not incepted".into()));
         }
+        let management_events = self
+            .db
+            .get_management_events(registry_id.prefix())
+            .into_iter()
+            .flatten();
+        let vc_events = self
+            .get_registry_vcs(registry_id)?
+            .into_iter()
+            .flat_map(|vc_id| self.db.get_events(&vc_id.prefix()).into_iter().flatten());
+        let mut failures = vec![];
+        for event in management_events.chain(vc_events) {
+            if let Err(e) = verify_anchor(&event, kel_events) {
+                failures.push((self.chain_digest(&event.event)?, e));
+            }
+        }
+        Ok(failures)
     }
 
-    pub fn get_management_event_at_sn(
+    /// Returns identifiers of vcs which issuance is anchored in registry
+    /// management event of sn between `from_sn` and `to_sn` inclusive.
+    pub fn vcs_anchored_in_management_range(
         &self,
-        id: &IdentifierPrefix,
-        sn: u64,
-    ) -> Result<Option<VerifiableEvent>, Error> {
-        match self.db.get_management_events(id) {
-            Some(mut events) => Ok(events.find(|event| {
-                if let Event::Management(man) = &event.event {
-                    man.sn == sn
-                } else {
-                    false
+        registry_id: &RegistryId,
+        from_sn: u64,
+        to_sn: u64,
+    ) -> Result<Vec<CredentialId>, Error> {
+        Ok(self
+            .get_registry_vcs(registry_id)?
+            .into_iter()
+            .filter(|vc_id| {
+                let issuance = self
+                    .db
+                    .get_events(&vc_id.prefix())
+                    .and_then(|mut events| events.next());
+                match issuance.map(|issuance| issuance.event) {
+                    Some(Event::Vc(vc)) => match vc.event.event_type {
+                        VCEventType::Bis(ref iss) => {
+                            (from_sn..=to_sn).contains(&iss.registry_anchor.sn)
+                        }
+                        _ => false,
+                    },
+                    _ => false,
                 }
-            })),
-            None => Ok(None),
-        }
+            })
+            .collect())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use keri::{derivation::self_addressing::SelfAddressing, prefix::IdentifierPrefix};
-
-    use crate::{
-        error::Error, event::verifiable_event::VerifiableEvent, processor::EventProcessor,
-        seal::EventSourceSeal, state::vc_state::TelState, tel::event_generator,
-    };
-
-    #[test]
-    pub fn test_processing() -> Result<(), Error> {
-        use std::fs;
-        use tempfile::Builder;
-        // Create test db and processor.
-        let root = Builder::new().prefix("test-db").tempdir().unwrap();
-        fs::create_dir_all(root.path()).unwrap();
-        let db = crate::database::EventDatabase::new(root.path()).unwrap();
-        let processor = EventProcessor::new(&db);
-
-        // Setup test data.
-        let message = "some message";
-        let message_id = SelfAddressing::Blake3_256.derive(message.as_bytes());
-        let issuer_prefix: IdentifierPrefix =
-            "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?;
-        let dummy_source_seal = EventSourceSeal {
-            sn: 1,
-            digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
-        };
+    /// Returns identifiers of all registries which management events are
+    /// stored.
+    pub fn registries(&self) -> Vec<RegistryId> {
+        self.db
+            .get_identifiers()
+            .filter(|id| matches!(self.stored_management_count(id), Ok(count) if count > 0))
+            .map(RegistryId::from)
+            .collect()
+    }
 
-        let vcp =
-            event_generator::make_inception_event(issuer_prefix, vec![], 0, vec![], None, None)?;
+    /// Returns number of vcs issued in given registry.
+    pub fn vc_count(&self, registry_id: &RegistryId) -> Result<usize, Error> {
+        Ok(self.get_registry_vcs(registry_id)?.len())
+    }
 
-        let management_tel_prefix = vcp.get_prefix();
+    /// Returns number of management events stored for given registry.
+    pub fn management_event_count(&self, registry_id: &RegistryId) -> Result<usize, Error> {
+        Ok(self.stored_management_count(registry_id.prefix())? as usize)
+    }
 
-        // before applying vcp to management tel, insert anchor event seal.
-        // note: source seal isn't check while event processing.
-        let verifiable_vcp = VerifiableEvent::new(vcp.clone(), dummy_source_seal.clone().into());
-        processor.process(verifiable_vcp.clone())?;
+    // Returns number of stored management events of registry, which is sn
+    // of the event expected next.
+    fn stored_management_count(&self, id: &IdentifierPrefix) -> Result<u64, Error> {
+        Ok(self
+            .db
+            .get_management_tip(id)?
+            .map(|tip| tip.count)
+            .unwrap_or(0))
+    }
 
-        // Check management state.
-        let st = processor.get_management_tel_state(&management_tel_prefix)?;
-        assert_eq!(st.sn, 0);
+    /// Checks if given identifier is current backer of registry.
+    pub fn is_backer(
+        &self,
+        registry_id: &RegistryId,
+        prefix: &IdentifierPrefix,
+    ) -> Result<bool, Error> {
+        Ok(self
+            .current_management_state(registry_id.prefix())?
+            .has_backer(prefix))
+    }
 
-        // check if vcp event is in db.
-        let man_event_from_db = processor.get_management_event_at_sn(&management_tel_prefix, 0)?;
-        assert!(man_event_from_db.is_some());
-        assert_eq!(man_event_from_db.unwrap(), verifiable_vcp);
+    /// Checks if registry doesn't use backers.
+    pub fn is_registry_backerless(&self, registry_id: &RegistryId) -> Result<bool, Error> {
+        Ok(self
+            .current_management_state(registry_id.prefix())?
+            .is_backerless())
+    }
 
-        // create issue event
-        let vc_prefix = IdentifierPrefix::SelfAddressing(message_id.clone());
-        let iss_event = event_generator::make_issuance_event(&st, message_id.clone(), None, None)?;
+    /// Returns identifier of issuer of given vc, resolved through registry
+    /// vc was issued in. `None` if vc is unknown. Vc issued in registry
+    /// which isn't known to database results in error.
+    pub fn issuer_of_vc(&self, vc_id: &CredentialId) -> Result<Option<IdentifierPrefix>, Error> {
+        let registry_id = match self
+            .db
+            .get_events(&vc_id.prefix())
+            .and_then(|mut events| events.next())
+        {
+            Some(VerifiableEvent {
+                event: Event::Vc(ref vc),
+                ..
+            }) => vc
+                .event
+                .registry_id()
+                .ok_or_else(|| Error::Generic("Vc issuance without registry".into()))?,
+            Some(_) => return Err(Error::Generic("Improper event type".into())),
+            None => return Ok(None),
+        };
+        if self.stored_management_count(&registry_id)? == 0 {
+            return Err(Error::Generic(format!(
+                "Unknown registry {}",
+                registry_id.to_str()
+            )));
+        }
+        Ok(Some(self.current_management_state(&registry_id)?.issuer))
+    }
 
-        let verifiable_iss =
-            VerifiableEvent::new(iss_event.clone(), dummy_source_seal.clone().into());
-        processor.process(verifiable_iss.clone())?;
+    /// Returns identifiers of vcs issued in registry which management events
+    /// aren't stored, for example because they weren't synced yet.
+    pub fn orphaned_vcs(&self) -> Result<Vec<CredentialId>, Error> {
+        let mut orphaned = vec![];
+        for id in self.db.get_identifiers() {
+            let vc_digest = match id {
+                IdentifierPrefix::SelfAddressing(ref vc_digest) => vc_digest.clone(),
+                _ => continue,
+            };
+            let issuance = self.db.get_events(&id).and_then(|mut events| events.next());
+            let registry_id = match issuance {
+                Some(VerifiableEvent {
+                    event: Event::Vc(ref vc),
+                    ..
+                }) => vc.event.registry_id(),
+                _ => None,
+            };
+            if let Some(registry_id) = registry_id {
+                if self.stored_management_count(&registry_id)? == 0 {
+                    orphaned.push(CredentialId::from(vc_digest));
+                }
+            }
+        }
+        Ok(orphaned)
+    }
 
-        // Chcek if iss event is in db.
-        let o = processor.get_events(&message_id)?;
-        assert_eq!(o, vec![verifiable_iss.clone()]);
+    /// Returns human readable summary of registry state.
+    pub fn status_report(&self, registry_id: &RegistryId) -> Result<String, Error> {
+        let state = self.get_management_tel_state(registry_id)?;
+        let (mut issued, mut revoked) = (0, 0);
+        for vc_id in self.get_registry_vcs(registry_id)? {
+            match self.get_vc_state(&vc_id)? {
+                TelState::Issued(_) => issued += 1,
+                TelState::Revoked => revoked += 1,
+                TelState::NotIsuued => (),
+            }
+        }
+        let backers = match state.backers {
+            Some(ref backers) => backers
+                .iter()
+                .map(|backer| backer.to_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "none (backerless registry)".into(),
+        };
+        let threshold = match state.backer_policy {
+            BackerPolicy::Count(n) => n.to_string(),
+            BackerPolicy::Subset(ref required) => format!(
+                "all of {}",
+                required
+                    .iter()
+                    .map(|backer| backer.to_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        Ok(format!(
+            "Registry: {}\nIssuer: {}\nSn: {}\nBackers: {}\nThreshold: {}\nIssued: {}\nRevoked: {}",
+            registry_id.prefix().to_str(),
+            state.issuer.to_str(),
+            state.sn,
+            backers,
+            threshold,
+            issued,
+            revoked
+        ))
+    }
 
-        let state =
-            processor.get_vc_state(&IdentifierPrefix::SelfAddressing(message_id.clone()))?;
-        assert!(matches!(state, TelState::Issued(_)));
-        let last = match state {
-            TelState::Issued(last) => last,
-            _ => vec![],
+    /// Dumps all management and vc tels from database into file at given path.
+    pub fn snapshot_to_path(&self, path: &Path) -> Result<(), Error> {
+        let mut snapshot = Snapshot {
+            management: vec![],
+            tels: vec![],
         };
+        for id in self.db.get_identifiers() {
+            if let Some(events) = self.db.get_management_events(&id) {
+                snapshot.management.push((id.clone(), events.collect()));
+            }
+            if let Some(events) = self.db.get_events(&id) {
+                snapshot.tels.push((id, events.collect()));
+            }
+        }
+        fs::write(path, serde_json::to_vec(&snapshot)?)?;
+        Ok(())
+    }
 
-        // Create revocation event.
-        let rev_event = event_generator::make_revoke_event(&message_id, &last, &st, None, None)?;
+    /// Copies events of other database, processing them as they were
+    /// received. Events already stored are skipped, and events which differ
+    /// from stored events of the same prefix and sn are reported as conflicts.
+    pub fn merge_from(&self, other: &EventDatabase) -> Result<MergeReport, Error> {
+        let mut report = MergeReport::default();
+        // Registries go first, so vc events can be checked against them.
+        for id in other.get_identifiers() {
+            let events = other.get_management_events(&id).into_iter().flatten();
+            self.merge_tel(
+                events,
+                |sn| Ok(self.management_event_at_sn(&id, sn)),
+                &mut report,
+            )?;
+        }
+        for id in other.get_identifiers() {
+            let events = other.get_events(&id).into_iter().flatten();
+            let stored_at = |sn| {
+                Ok(self
+                    .db
+                    .get_events(&id)
+                    .and_then(|mut events| events.find(|event| event.event.get_sn() == sn)))
+            };
+            self.merge_tel(events, stored_at, &mut report)?;
+        }
+        Ok(report)
+    }
 
-        let verifiable_rev =
-            VerifiableEvent::new(rev_event.clone(), dummy_source_seal.clone().into());
+    // Process events of single tel, comparing them with events already
+    // stored at their sn.
+    fn merge_tel(
+        &self,
+        events: impl Iterator<Item = VerifiableEvent>,
+        stored_at: impl Fn(u64) -> Result<Option<VerifiableEvent>, Error>,
+        report: &mut MergeReport,
+    ) -> Result<(), Error> {
+        for event in events {
+            match stored_at(event.event.get_sn())? {
+                Some(stored) if stored.event.serialize()? == event.event.serialize()? => {
+                    report.duplicates += 1;
+                }
+                Some(_) => {
+                    report
+                        .conflicts
+                        .push((event.event.get_prefix(), event.event.get_sn()));
+                    break;
+                }
+                None => {
+                    self.process(event)?;
+                    report.merged += 1;
+                }
+            }
+        }
+        Ok(())
+    }
 
-        // Check if vc was revoked.
-        processor.process(verifiable_rev.clone())?;
-        let state = processor.get_vc_state(&vc_prefix)?;
-        assert!(matches!(state, TelState::Revoked));
+    /// Returns db value stored for management tel of given identifier, as it is.
+    pub fn raw_management_bytes(&self, registry_id: &RegistryId) -> Result<Option<Vec<u8>>, Error> {
+        self.db.get_raw_management_events(registry_id.prefix())
+    }
 
-        // Chcek if rev event is in db.
-        let o = processor.get_events(&message_id)?;
-        assert_eq!(o.len(), 2);
-        assert_eq!(o, vec![verifiable_iss, verifiable_rev]);
+    /// Returns db value stored for vc tel of given identifier, as it is.
+    pub fn raw_vc_bytes(&self, vc_id: &CredentialId) -> Result<Option<Vec<u8>>, Error> {
+        self.db.get_raw_events(&vc_id.prefix())
+    }
 
-        let backers: Vec<IdentifierPrefix> =
-            vec!["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?];
+    /// Returns digest of the latest stored vc event, equal to `p` of the
+    /// event which would follow it.
+    pub fn vc_tip_digest(
+        &self,
+        vc_id: &CredentialId,
+    ) -> Result<Option<SelfAddressingPrefix>, Error> {
+        self.db
+            .get_tip(&vc_id.prefix())?
+            .map(|tip| self.chain_digest(&tip.last.event))
+            .transpose()
+    }
 
-        let vrt = event_generator::make_rotation_event(&st, &backers, &vec![], None, None)?;
+    /// Returns digest of the latest stored management event, equal to `p`
+    /// of the rotation which would follow it.
+    pub fn management_tip_digest(
+        &self,
+        registry_id: &RegistryId,
+    ) -> Result<Option<SelfAddressingPrefix>, Error> {
+        self.db
+            .get_management_tip(registry_id.prefix())?
+            .map(|tip| self.chain_digest(&tip.last.event))
+            .transpose()
+    }
 
-        let verifiable_vrt = VerifiableEvent::new(vrt.clone(), dummy_source_seal.clone().into());
-        processor.process(verifiable_vrt.clone())?;
+    /// Returns source seal of vc event of given sn, or `None` if event isn't
+    /// anchored yet.
+    pub fn get_source_seal(
+        &self,
+        vc_id: &CredentialId,
+        sn: u64,
+    ) -> Result<Option<EventSourceSeal>, Error> {
+        Ok(self
+            .db
+            .get_events(&vc_id.prefix())
+            .and_then(|mut events| events.find(|event| event.event.get_sn() == sn))
+            .and_then(|event| event.seal)
+            .map(|attached| attached.seal))
+    }
 
-        // Check management state.
-        let st = processor.get_management_tel_state(&management_tel_prefix)?;
-        assert_eq!(st.sn, 1);
+    /// Anchors already processed vc event of given sn, which was stored
+    /// without source seal. Seal attached to event can't be replaced, because
+    /// it may have been already verified against KEL.
+    pub fn update_source_seal(
+        &self,
+        vc_id: &CredentialId,
+        sn: u64,
+        new_seal: EventSourceSeal,
+    ) -> Result<(), Error> {
+        let vc_id = &vc_id.prefix();
+        let prefix_lock = self.prefix_lock(vc_id)?;
+        let _prefix_guard = lock(&prefix_lock)?;
+        let mut events: Vec<VerifiableEvent> =
+            self.db.get_events(vc_id).into_iter().flatten().collect();
+        let event = events
+            .iter_mut()
+            .find(|event| event.event.get_sn() == sn)
+            .ok_or_else(|| Error::Generic(format!("No vc event at sn {}", sn)))?;
+        if event.seal.is_some() {
+            return Err(Error::WrongState(format!(
+                "Vc event at sn {} is already anchored",
+                sn
+            )));
+        }
+        event.seal = Some(new_seal.into());
+        self.db.set_events(events, vc_id)
+    }
+
+    /// Returns event which revoked vc, or `None` if vc isn't revoked.
+    pub fn get_revocation_event(&self, vc_id: &CredentialId) -> Result<Option<VCEvent>, Error> {
+        if self.get_vc_state(vc_id)? != TelState::Revoked {
+            return Ok(None);
+        }
+        Ok(self.db.get_events(&vc_id.prefix()).and_then(|mut events| {
+            events.find_map(|event| match event.event {
+                Event::Vc(vc) => match vc.event.event_type {
+                    VCEventType::Rev(_) | VCEventType::Brv(_) => Some(vc.event),
+                    _ => None,
+                },
+                _ => None,
+            })
+        }))
+    }
 
-        // check if vrt event is in db.
-        let man_event_from_db = processor.get_management_event_at_sn(&management_tel_prefix, 1)?;
-        assert!(man_event_from_db.is_some());
-        assert_eq!(man_event_from_db.unwrap(), verifiable_vrt);
+    /// Walks vc events in sn order and returns the first one matching given
+    /// predicate, with its sn. Events following the match aren't loaded.
+    pub fn find_vc_event(
+        &self,
+        vc_id: &CredentialId,
+        pred: impl Fn(&VCEvent) -> bool,
+    ) -> Result<Option<(u64, VCEvent)>, Error> {
+        Ok(self.db.get_events(&vc_id.prefix()).and_then(|mut events| {
+            events.find_map(|event| match event.event {
+                Event::Vc(vc) if pred(&vc.event) => Some((vc.event.sn, vc.event)),
+                _ => None,
+            })
+        }))
+    }
 
-        Ok(())
+    pub fn get_escrowed_management_events(
+        &self,
+        registry_id: &RegistryId,
+    ) -> Result<Vec<VerifiableEvent>, Error> {
+        Ok(self.escrowed_management_events(registry_id.prefix()))
+    }
+
+    /// Returns stored events of types unknown to this version of the
+    /// library, which refer to given identifier.
+    pub fn get_unknown_events(&self, id: &IdentifierPrefix) -> Result<Vec<VerifiableEvent>, Error> {
+        match self.db.get_unknown_events(id) {
+            Some(events) => Ok(events.collect()),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn escrowed_management_events(&self, id: &IdentifierPrefix) -> Vec<VerifiableEvent> {
+        match self.db.get_escrowed_management_events(id) {
+            Some(events) => events.collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn get_events(&self, vc_id: &CredentialId) -> Result<Vec<VerifiableEvent>, Error> {
+        match self.db.get_events(&vc_id.prefix()) {
+            Some(events) => Ok(events.collect()),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub fn get_management_event_at_sn(
+        &self,
+        registry_id: &RegistryId,
+        sn: u64,
+    ) -> Result<Option<VerifiableEvent>, Error> {
+        Ok(self.management_event_at_sn(registry_id.prefix(), sn))
+    }
+
+    fn management_event_at_sn(&self, id: &IdentifierPrefix, sn: u64) -> Option<VerifiableEvent> {
+        self.db.get_management_events(id).and_then(|mut events| {
+            events.find(|event| {
+                if let Event::Management(man) = &event.event {
+                    man.sn == sn
+                } else {
+                    false
+                }
+            })
+        })
     }
 }
+
+#[cfg(test)]
+mod tests;
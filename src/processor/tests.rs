@@ -0,0 +1,2235 @@
+use keri::{
+    derivation::self_addressing::SelfAddressing, event::sections::seal::EventSeal,
+    event_message::EventMessage, prefix::IdentifierPrefix,
+};
+
+use crate::{
+    error::Error,
+    event::{manager_event::Config, verifiable_event::VerifiableEvent},
+    id::{CredentialId, RegistryId},
+    processor::EventProcessor,
+    seal::{AttachedSourceSeal, EventSourceSeal},
+    state::vc_state::TelState,
+    tel::event_generator,
+    test_utils::{dummy_source_seal, incept_registry, test_db},
+};
+
+// Incept registry with backers and return its identifier.
+fn incept_backed_registry(
+    processor: &EventProcessor,
+    issuer: &str,
+    backers: &[IdentifierPrefix],
+) -> Result<RegistryId, Error> {
+    let vcp = event_generator::make_inception_event(
+        issuer.parse()?,
+        vec![],
+        0,
+        backers.to_vec(),
+        None,
+        None,
+    )?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+    Ok(registry_id)
+}
+
+// Issue vc of given content in registry and return vc identifier.
+fn issue(
+    processor: &EventProcessor,
+    registry_id: &RegistryId,
+    vc: &str,
+) -> Result<CredentialId, Error> {
+    let st = processor.get_management_tel_state(registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+    processor.process(VerifiableEvent::new(iss, dummy_source_seal()?.into()))?;
+    Ok(CredentialId::from(vc_hash))
+}
+
+// Revoke vc issued by `issue`.
+fn revoke(processor: &EventProcessor, registry_id: &RegistryId, vc: &str) -> Result<(), Error> {
+    let st = processor.get_management_tel_state(registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes());
+    let last = match processor.get_vc_state(&CredentialId::from(vc_hash.clone()))? {
+        TelState::Issued(last) => last,
+        _ => return Err(Error::Generic("Vc not issued".into())),
+    };
+    let rev = event_generator::make_revoke_event(&vc_hash, &last, &st, None, None)?;
+    processor.process(VerifiableEvent::new(rev, dummy_source_seal()?.into()))?;
+    Ok(())
+}
+
+// Make interaction event of issuer KEL, sealing given TEL events.
+fn make_ixn(
+    issuer: &str,
+    sn: u64,
+    tel_events: &[&crate::event::Event],
+) -> Result<EventMessage, Error> {
+    let seals = tel_events
+        .iter()
+        .map(|event| {
+            Ok(EventSeal {
+                prefix: event.get_prefix(),
+                sn: event.get_sn(),
+                event_digest: SelfAddressing::Blake3_256.derive(&event.serialize()?),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let ixn_raw = format!(
+        r#"{{"v":"KERI10JSON000000_","i":"{}","s":"{:x}","t":"ixn","p":"EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8","a":{}}}"#,
+        issuer,
+        sn,
+        serde_json::to_string(&seals)?
+    );
+    Ok(serde_json::from_str(&ixn_raw)?)
+}
+
+// Make source seal pointing to given KEL event.
+fn kel_source_seal(kel_event: &EventMessage) -> Result<AttachedSourceSeal, Error> {
+    Ok(AttachedSourceSeal::new(
+        kel_event.event.sn,
+        SelfAddressing::Blake3_256.derive(&kel_event.serialize()?),
+    ))
+}
+
+#[test]
+pub fn test_processing() -> Result<(), Error> {
+    use std::fs;
+    use tempfile::Builder;
+    // Create test db and processor.
+    let root = Builder::new().prefix("test-db").tempdir().unwrap();
+    fs::create_dir_all(root.path()).unwrap();
+    let db = crate::database::EventDatabase::new(root.path()).unwrap();
+    let processor = EventProcessor::new(&db);
+
+    // Setup test data.
+    let message = "some message";
+    let message_id = SelfAddressing::Blake3_256.derive(message.as_bytes());
+    let issuer_prefix: IdentifierPrefix = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?;
+    let dummy_source_seal = EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    };
+
+    let vcp = event_generator::make_inception_event(
+        issuer_prefix,
+        vec![],
+        0,
+        vec!["DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?],
+        None,
+        None,
+    )?;
+
+    let management_tel_prefix = RegistryId::from(vcp.get_prefix());
+
+    // before applying vcp to management tel, insert anchor event seal.
+    // note: source seal isn't check while event processing.
+    let verifiable_vcp = VerifiableEvent::new(vcp.clone(), dummy_source_seal.clone().into());
+    processor.process(verifiable_vcp.clone())?;
+
+    // Check management state.
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    assert_eq!(st.sn, 0);
+
+    // check if vcp event is in db.
+    let man_event_from_db = processor.get_management_event_at_sn(&management_tel_prefix, 0)?;
+    assert!(man_event_from_db.is_some());
+    assert_eq!(man_event_from_db.unwrap(), verifiable_vcp);
+
+    // create issue event
+    let vc_prefix = CredentialId::from(message_id.clone());
+    let iss_event = event_generator::make_issuance_event(&st, message_id.clone(), None, None)?;
+
+    let verifiable_iss = VerifiableEvent::new(iss_event.clone(), dummy_source_seal.clone().into());
+    processor.process(verifiable_iss.clone())?;
+
+    // Chcek if iss event is in db.
+    let o = processor.get_events(&vc_prefix)?;
+    assert_eq!(o, vec![verifiable_iss.clone()]);
+
+    let state = processor.get_vc_state(&CredentialId::from(message_id.clone()))?;
+    assert!(matches!(state, TelState::Issued(_)));
+    let last = match state {
+        TelState::Issued(last) => last,
+        _ => vec![],
+    };
+
+    // Create revocation event.
+    let rev_event = event_generator::make_revoke_event(&message_id, &last, &st, None, None)?;
+
+    let verifiable_rev = VerifiableEvent::new(rev_event.clone(), dummy_source_seal.clone().into());
+
+    // Check if vc was revoked.
+    processor.process(verifiable_rev.clone())?;
+    let state = processor.get_vc_state(&vc_prefix)?;
+    assert!(matches!(state, TelState::Revoked));
+
+    // Chcek if rev event is in db.
+    let o = processor.get_events(&vc_prefix)?;
+    assert_eq!(o.len(), 2);
+    assert_eq!(o, vec![verifiable_iss, verifiable_rev]);
+
+    let backers: Vec<IdentifierPrefix> =
+        vec!["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?];
+
+    let vrt = event_generator::make_rotation_event(&st, &backers, &vec![], None, None)?;
+
+    let verifiable_vrt = VerifiableEvent::new(vrt.clone(), dummy_source_seal.clone().into());
+    processor.process(verifiable_vrt.clone())?;
+
+    // Check management state.
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    assert_eq!(st.sn, 1);
+
+    // check if vrt event is in db.
+    let man_event_from_db = processor.get_management_event_at_sn(&management_tel_prefix, 1)?;
+    assert!(man_event_from_db.is_some());
+    assert_eq!(man_event_from_db.unwrap(), verifiable_vrt);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_out_of_order_management_event() -> Result<(), Error> {
+    use crate::event::{
+        manager_event::{ManagerEventType, ManagerTelEvent, Rot},
+        Event,
+    };
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let management_tel_prefix = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    let vrt = event_generator::make_rotation_event(&st, &[], &[], None, None)?;
+    processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    assert_eq!(st.sn, 1);
+
+    // Rotation bound to current tip, but with sn jumping ahead.
+    let future_vrt = Event::Management(ManagerTelEvent::new(
+        management_tel_prefix.prefix(),
+        5,
+        ManagerEventType::Vrt(Rot {
+            prev_event: SelfAddressing::Blake3_256.derive(&st.last),
+            backers_to_add: vec![],
+            backers_to_remove: vec![],
+            backer_threshold: None,
+        }),
+        SerializationFormats::JSON,
+    )?);
+    let verifiable_future_vrt = VerifiableEvent::new(future_vrt, dummy_source_seal()?.into());
+    let result = processor.process(verifiable_future_vrt.clone());
+    assert!(matches!(
+        result,
+        Err(Error::OutOfOrder {
+            expected: 2,
+            got: 5
+        })
+    ));
+
+    // State is unchanged and event is kept in escrow.
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    assert_eq!(st.sn, 1);
+    assert_eq!(
+        processor.get_escrowed_management_events(&management_tel_prefix)?,
+        vec![verifiable_future_vrt]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_escrowed_rotation_applied() -> Result<(), Error> {
+    use crate::{event::Event, state::ManagerTelState};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let apply = |state: &ManagerTelState, event: &Event| match event {
+        Event::Management(ref man) => state.apply(man),
+        _ => Err(Error::Generic("Improper event type".into())),
+    };
+
+    // Two rotations, received in reverse order.
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let first_backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let first_vrt = event_generator::make_rotation_event(&st, &[first_backer], &[], None, None)?;
+    let st = apply(&st, &first_vrt)?;
+    let second_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+    let second_vrt = event_generator::make_rotation_event(&st, &[second_backer], &[], None, None)?;
+    let expected_state = apply(&st, &second_vrt)?;
+
+    let result = processor.process(VerifiableEvent::new(
+        second_vrt,
+        dummy_source_seal()?.into(),
+    ));
+    assert!(matches!(
+        result,
+        Err(Error::OutOfOrder {
+            expected: 1,
+            got: 2
+        })
+    ));
+    assert_eq!(
+        processor
+            .get_escrowed_management_events(&registry_id)?
+            .len(),
+        1
+    );
+
+    // Escrowed rotation is applied once its predecessor arrives.
+    processor.process(VerifiableEvent::new(first_vrt, dummy_source_seal()?.into()))?;
+    assert_eq!(
+        processor.get_management_tel_state(&registry_id)?,
+        expected_state
+    );
+    assert!(processor
+        .get_escrowed_management_events(&registry_id)?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_escrow_capacity() -> Result<(), Error> {
+    use crate::{
+        event::{
+            manager_event::{ManagerEventType, ManagerTelEvent, Rot},
+            Event,
+        },
+        processor::MAX_ESCROW_DISTANCE,
+    };
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+
+    // Flood of rotations from the future, which can't be bound to any
+    // stored event. Only the ones close enough to the tip are kept.
+    for sn in 2..(MAX_ESCROW_DISTANCE + 10) {
+        let future_vrt = ManagerTelEvent::new(
+            registry_id.prefix(),
+            sn,
+            ManagerEventType::Vrt(Rot {
+                prev_event: SelfAddressing::Blake3_256.derive(&sn.to_be_bytes()),
+                backers_to_add: vec![],
+                backers_to_remove: vec![],
+                backer_threshold: None,
+            }),
+            SerializationFormats::JSON,
+        )?;
+        let verifiable =
+            VerifiableEvent::new(Event::Management(future_vrt), dummy_source_seal()?.into());
+        assert!(matches!(
+            processor.process(verifiable.clone()),
+            Err(Error::OutOfOrder { .. })
+        ));
+        // Resubmitted event isn't escrowed twice.
+        assert!(processor.process(verifiable).is_err());
+    }
+    assert_eq!(
+        processor
+            .get_escrowed_management_events(&registry_id)?
+            .len(),
+        MAX_ESCROW_DISTANCE as usize
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_escrow_drain_failure() -> Result<(), Error> {
+    use crate::{event::Event, state::State};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let first_vrt = event_generator::make_rotation_event(&st, &[], &[], None, None)?;
+    let st = match first_vrt {
+        Event::Management(ref man) => st.apply(man)?,
+        _ => unreachable!(),
+    };
+    let second_vrt = event_generator::make_rotation_event(&st, &[], &[], None, None)?;
+
+    // Escrowed rotation without anchor, which processor doesn't accept.
+    db.add_escrowed_management_event(
+        VerifiableEvent::unanchored(second_vrt),
+        registry_id.prefix(),
+    )?;
+
+    // Failure of draining escrow doesn't fail processing of stored event.
+    let state = processor.process(VerifiableEvent::new(first_vrt, dummy_source_seal()?.into()))?;
+    assert!(matches!(state, State::Management(ref st) if st.sn == 1));
+    assert_eq!(
+        processor
+            .get_escrowed_management_events(&registry_id)?
+            .len(),
+        1
+    );
+
+    // Rotation is kept in escrow, so it can be processed later.
+    let permissive = EventProcessor::new(&db).accept_unanchored(true);
+    permissive.process_escrowed(&registry_id)?;
+    assert_eq!(processor.get_management_tel_state(&registry_id)?.sn, 2);
+    assert!(processor
+        .get_escrowed_management_events(&registry_id)?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_escrow_poisoning() -> Result<(), Error> {
+    use crate::{
+        event::{
+            manager_event::{ManagerEventType, ManagerTelEvent, Rot},
+            Event,
+        },
+        state::ManagerTelState,
+    };
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let apply = |state: &ManagerTelState, event: &Event| match event {
+        Event::Management(ref man) => state.apply(man),
+        _ => Err(Error::Generic("Improper event type".into())),
+    };
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let first_backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let first_vrt = event_generator::make_rotation_event(&st, &[first_backer], &[], None, None)?;
+    let st = apply(&st, &first_vrt)?;
+    let second_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+    let second_vrt = event_generator::make_rotation_event(&st, &[second_backer], &[], None, None)?;
+    let expected_state = apply(&st, &second_vrt)?;
+
+    // Forged rotation takes sn of the second rotation in escrow first.
+    let forged_vrt = ManagerTelEvent::new(
+        registry_id.prefix(),
+        2,
+        ManagerEventType::Vrt(Rot {
+            prev_event: SelfAddressing::Blake3_256.derive(b"forged"),
+            backers_to_add: vec![],
+            backers_to_remove: vec![],
+            backer_threshold: None,
+        }),
+        SerializationFormats::JSON,
+    )?;
+    for vrt in vec![Event::Management(forged_vrt), second_vrt] {
+        assert!(matches!(
+            processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into())),
+            Err(Error::OutOfOrder { .. })
+        ));
+    }
+    assert_eq!(
+        processor
+            .get_escrowed_management_events(&registry_id)?
+            .len(),
+        2
+    );
+
+    // Forged rotation is dropped and the second one applied, once their
+    // predecessor arrives.
+    processor.process(VerifiableEvent::new(first_vrt, dummy_source_seal()?.into()))?;
+    assert_eq!(
+        processor.get_management_tel_state(&registry_id)?,
+        expected_state
+    );
+    assert!(processor
+        .get_escrowed_management_events(&registry_id)?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_issuing_after_rotation() -> Result<(), Error> {
+    use crate::event::{
+        vc_event::{Issuance, TimestampedVCEvent, VCEvent, VCEventType},
+        Event,
+    };
+    use keri::event::{sections::seal::EventSeal, SerializationFormats};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let management_tel_prefix = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let vcp = processor
+        .get_management_event_at_sn(&management_tel_prefix, 0)?
+        .unwrap()
+        .event;
+
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    let backers: Vec<IdentifierPrefix> =
+        vec!["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?];
+    let vrt = event_generator::make_rotation_event(&st, &backers, &[], None, None)?;
+    processor.process(VerifiableEvent::new(
+        vrt.clone(),
+        dummy_source_seal()?.into(),
+    ))?;
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+
+    // Issue vc under rotated registry.
+    let message_id = SelfAddressing::Blake3_256.derive("some message".as_bytes());
+    let iss_event = event_generator::make_issuance_event(&st, message_id.clone(), None, None)?;
+    if let Event::Vc(TimestampedVCEvent {
+        event: VCEvent {
+            event_type: VCEventType::Bis(ref iss),
+            ..
+        },
+        ..
+    }) = iss_event
+    {
+        assert_eq!(&iss.registry_anchor.prefix, management_tel_prefix.prefix());
+        assert_eq!(iss.registry_anchor.sn, 1);
+        assert!(iss
+            .registry_anchor
+            .event_digest
+            .verify_binding(&vrt.serialize()?));
+    } else {
+        panic!("Expected bis event");
+    }
+    processor.process(VerifiableEvent::new(iss_event, dummy_source_seal()?.into()))?;
+    let state = processor.get_vc_state(&CredentialId::from(message_id))?;
+    assert!(matches!(state, TelState::Issued(_)));
+
+    // Issuance anchored at sn 1, but pointing to inception event is rejected.
+    let other_message_id = SelfAddressing::Blake3_256.derive("other message".as_bytes());
+    let wrong_anchor = EventSeal {
+        prefix: management_tel_prefix.into(),
+        sn: 1,
+        event_digest: SelfAddressing::Blake3_256.derive(&vcp.serialize()?),
+    };
+    let bad_iss = Event::Vc(TimestampedVCEvent::new(VCEvent::new(
+        IdentifierPrefix::SelfAddressing(other_message_id),
+        0,
+        VCEventType::Bis(Issuance::new(wrong_anchor)),
+        SerializationFormats::JSON,
+    )?)?);
+    assert!(processor
+        .process(VerifiableEvent::new(bad_iss, dummy_source_seal()?.into()))
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_snapshot_and_restore() -> Result<(), Error> {
+    let (root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+
+    // Setup two registries and issue vc in the first one.
+    let first_registry =
+        incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let second_registry =
+        incept_registry(&processor, "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+    let vc_prefix = issue(&processor, &first_registry, "some message")?;
+
+    let snapshot_path = root.path().join("snapshot");
+    processor.snapshot_to_path(&snapshot_path)?;
+
+    // Restore into fresh database.
+    let (_restored_root, restored_db) = test_db();
+    EventProcessor::restore_from_path(&restored_db, &snapshot_path)?;
+    // Restoring again replaces restored events instead of duplicating them.
+    EventProcessor::restore_from_path(&restored_db, &snapshot_path)?;
+    let restored_processor = EventProcessor::new(&restored_db);
+
+    for registry in [first_registry, second_registry].iter() {
+        assert!(
+            processor.get_management_tel_state(registry)?
+                == restored_processor.get_management_tel_state(registry)?
+        );
+        assert_eq!(
+            processor.get_management_event_at_sn(registry, 0)?,
+            restored_processor.get_management_event_at_sn(registry, 0)?
+        );
+        assert_eq!(restored_processor.management_event_count(registry)?, 1);
+    }
+    assert_eq!(
+        processor.get_events(&vc_prefix)?,
+        restored_processor.get_events(&vc_prefix)?
+    );
+    assert_eq!(
+        restored_processor.get_vc_state(&vc_prefix)?,
+        processor.get_vc_state(&vc_prefix)?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_restore_into_warm_processor() -> Result<(), Error> {
+    let (root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+    let snapshot_path = root.path().join("snapshot");
+    processor.snapshot_to_path(&snapshot_path)?;
+
+    // Processing revocation caches revoked state, which doesn't reflect
+    // restored events.
+    revoke(&processor, &registry_id, "some vc")?;
+    EventProcessor::restore_from_path(&db, &snapshot_path)?;
+    assert!(matches!(
+        processor.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    // Vc can be revoked again.
+    revoke(&processor, &registry_id, "some vc")?;
+    assert_eq!(processor.get_vc_state(&vc_id)?, TelState::Revoked);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_unsupported_version() -> Result<(), Error> {
+    use crate::event::{manager_event::ManagerTelEvent, Event};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+
+    let vcp_raw = r#"{"v":"KERI20JSON0000ad_","i":"EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs","ii":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"0","t":"vcp","c":["NB"],"bt":"0","b":[]}"#;
+    let vcp: ManagerTelEvent = serde_json::from_str(vcp_raw).unwrap();
+    let result = processor.process(VerifiableEvent::new(
+        Event::Management(vcp.clone()),
+        dummy_source_seal()?.into(),
+    ));
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedVersion { major: 2, minor: 0 })
+    ));
+    assert!(processor
+        .get_management_event_at_sn(&vcp.prefix.clone().into(), 0)?
+        .is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_unknown_event() -> Result<(), Error> {
+    use crate::{event::Event, state::State};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let raw = r#"{"v":"KERI10JSON000092_","i":"EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs","s":"0","t":"vdp","di":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM"}"#;
+    let event = Event::Unknown {
+        t: "vdp".into(),
+        raw: raw.as_bytes().to_vec(),
+    };
+    let prefix = event.get_prefix();
+    let verifiable = VerifiableEvent::new(event, dummy_source_seal()?.into());
+
+    // Event is stored, so it can be forwarded, but isn't applied to state.
+    let result = processor.process(verifiable.clone());
+    assert!(matches!(result, Ok(State::Unknown)));
+    assert_eq!(
+        processor.management_event_count(&RegistryId::from(prefix.clone()))?,
+        0
+    );
+    assert_eq!(
+        processor.get_unknown_events(&prefix)?,
+        vec![verifiable.clone()]
+    );
+
+    // The same event isn't stored twice.
+    processor.process(verifiable.clone())?;
+    assert_eq!(processor.get_unknown_events(&prefix)?, vec![verifiable]);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_size_mismatch() -> Result<(), Error> {
+    use crate::event::Event;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let mut tampered_vcp = match vcp {
+        Event::Management(ref man) => man.clone(),
+        _ => unreachable!(),
+    };
+    let size = tampered_vcp.serialization_info.size;
+    tampered_vcp.serialization_info.size = size + 1;
+
+    let result = processor.process(VerifiableEvent::new(
+        Event::Management(tampered_vcp.clone()),
+        dummy_source_seal()?.into(),
+    ));
+    assert!(
+        matches!(result, Err(Error::SizeMismatch { declared, actual })
+        if declared == size + 1 && actual == size)
+    );
+    assert!(processor
+        .get_management_event_at_sn(&tampered_vcp.prefix.clone().into(), 0)?
+        .is_none());
+
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+
+    Ok(())
+}
+
+#[test]
+pub fn test_revocation_anchored_before_issuance() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let management_tel_prefix =
+        incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let st = processor.get_management_tel_state(&management_tel_prefix)?;
+    let source_seal_at = |sn: u64| -> Result<EventSourceSeal, Error> {
+        Ok(EventSourceSeal {
+            sn,
+            ..dummy_source_seal()?
+        })
+    };
+
+    let message_id = SelfAddressing::Blake3_256.derive("some message".as_bytes());
+    let vc_prefix = CredentialId::from(message_id.clone());
+    let iss_event = event_generator::make_issuance_event(&st, message_id.clone(), None, None)?;
+    processor.process(VerifiableEvent::new(iss_event, source_seal_at(10)?.into()))?;
+
+    let last = match processor.get_vc_state(&vc_prefix)? {
+        TelState::Issued(last) => last,
+        _ => vec![],
+    };
+    let rev_event = event_generator::make_revoke_event(&message_id, &last, &st, None, None)?;
+
+    // Revocation anchored at KEL sn 8, before issuance anchored at sn 10.
+    let result = processor.process(VerifiableEvent::new(
+        rev_event.clone(),
+        source_seal_at(8)?.into(),
+    ));
+    assert!(result.is_err());
+    assert!(matches!(
+        processor.get_vc_state(&vc_prefix)?,
+        TelState::Issued(_)
+    ));
+
+    processor.process(VerifiableEvent::new(rev_event, source_seal_at(11)?.into()))?;
+    assert_eq!(processor.get_vc_state(&vc_prefix)?, TelState::Revoked);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_raw_bytes() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let management_tel_prefix =
+        incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_prefix = issue(&processor, &management_tel_prefix, "some message")?;
+
+    let raw = processor
+        .raw_management_bytes(&management_tel_prefix)?
+        .unwrap();
+    let stored: Vec<VerifiableEvent> = serde_cbor::from_slice(&raw).unwrap();
+    assert_eq!(
+        stored,
+        vec![processor
+            .get_management_event_at_sn(&management_tel_prefix, 0)?
+            .unwrap()]
+    );
+
+    let raw = processor.raw_vc_bytes(&vc_prefix)?.unwrap();
+    let stored: Vec<VerifiableEvent> = serde_cbor::from_slice(&raw).unwrap();
+    assert_eq!(stored, processor.get_events(&vc_prefix)?);
+    assert_eq!(stored.len(), 1);
+
+    // Nothing stored for unknown identifier.
+    let unknown = CredentialId::from(SelfAddressing::Blake3_256.derive("unknown vc".as_bytes()));
+    assert!(processor.raw_vc_bytes(&unknown)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_status_report() -> Result<(), Error> {
+    use keri::prefix::Prefix;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let issuer_prefix = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY";
+    let management_tel_prefix = incept_registry(&processor, issuer_prefix)?;
+
+    // Issue two vcs and revoke one of them.
+    issue(&processor, &management_tel_prefix, "first message")?;
+    issue(&processor, &management_tel_prefix, "second message")?;
+    revoke(&processor, &management_tel_prefix, "first message")?;
+
+    let report = processor.status_report(&management_tel_prefix)?;
+    assert!(report.contains(&management_tel_prefix.prefix().to_str()));
+    assert!(report.contains(issuer_prefix));
+    assert!(report.contains("Issued: 1"));
+    assert!(report.contains("Revoked: 1"));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_revocation_from_other_registry() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let first_registry =
+        incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let second_registry =
+        incept_registry(&processor, "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+
+    // Issue vc in first registry.
+    let vc_prefix = issue(&processor, &first_registry, "some message")?;
+    let last = match processor.get_vc_state(&vc_prefix)? {
+        TelState::Issued(last) => last,
+        _ => vec![],
+    };
+
+    // Try to revoke it with event anchored in second registry.
+    let second_state = processor.get_management_tel_state(&second_registry)?;
+    let rev_event =
+        event_generator::make_revoke_event(vc_prefix.digest(), &last, &second_state, None, None)?;
+    let result = processor.process(VerifiableEvent::new(rev_event, dummy_source_seal()?.into()));
+    assert!(matches!(result, Err(Error::WrongState(_))));
+    assert!(matches!(
+        processor.get_vc_state(&vc_prefix)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_events_after_revocation() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let management_tel_prefix =
+        incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_prefix = issue(&processor, &management_tel_prefix, "some message")?;
+    revoke(&processor, &management_tel_prefix, "some message")?;
+
+    // Store duplicated revocation, bypassing processor checks.
+    let revocation = processor.get_events(&vc_prefix)?.pop().unwrap();
+    db.add_new_event(revocation, &vc_prefix.prefix())?;
+    assert_eq!(processor.get_events(&vc_prefix)?.len(), 3);
+    assert_eq!(processor.get_vc_state(&vc_prefix)?, TelState::Revoked);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_get_revocation_event() -> Result<(), Error> {
+    use crate::event::vc_event::VCEventType;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+
+    let revoked_vc = issue(&processor, &registry_id, "revoked message")?;
+    revoke(&processor, &registry_id, "revoked message")?;
+    let issued_vc = issue(&processor, &registry_id, "issued message")?;
+
+    let revocation = processor.get_revocation_event(&revoked_vc)?.unwrap();
+    assert_eq!(revocation.prefix, revoked_vc.prefix());
+    assert_eq!(revocation.sn, 1);
+    assert!(matches!(revocation.event_type, VCEventType::Brv(_)));
+
+    assert!(processor.get_revocation_event(&issued_vc)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_counts() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let other_registry_id =
+        incept_registry(&processor, "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+
+    for vc in ["first", "second", "third"].iter() {
+        issue(&processor, &registry_id, vc)?;
+    }
+    issue(&processor, &other_registry_id, "other")?;
+
+    assert_eq!(processor.vc_count(&registry_id)?, 3);
+    assert_eq!(processor.vc_count(&other_registry_id)?, 1);
+    assert_eq!(processor.management_event_count(&registry_id)?, 1);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_incremental_state() -> Result<(), Error> {
+    use crate::state::State;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    // Backer which stays in registry during rotations, so it can witness
+    // issuance.
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &["EXvR3p8V95W8J7Ui4-mEzZ79S-A1esAnJo1Kmzq80Jkc".parse()?],
+    )?;
+
+    let backers: Vec<IdentifierPrefix> = vec![
+        "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?,
+        "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?,
+        "Dvxo-P4W_Z0xXTfoA3_4DMPn7oi0mLCElOWJDpC0nQXw".parse()?,
+    ];
+    let mut last_state = None;
+    for i in 0..10 {
+        let st = processor.get_management_tel_state(&registry_id)?;
+        let (ba, br) = if i % 2 == 0 {
+            (vec![backers[i % 3].clone()], vec![])
+        } else {
+            (vec![], vec![backers[(i - 1) % 3].clone()])
+        };
+        let vrt = event_generator::make_rotation_event(&st, &ba, &br, None, None)?;
+        last_state =
+            Some(processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?);
+    }
+    match last_state {
+        Some(State::Management(incremental)) => {
+            assert_eq!(incremental.sn, 10);
+            assert_eq!(
+                incremental,
+                processor.get_management_tel_state(&registry_id)?
+            );
+        }
+        _ => panic!("Expected management state"),
+    }
+
+    // Fresh processor folds events from db and gets the same state.
+    let vc_id = issue(&processor, &registry_id, "some message")?;
+    let other_processor = EventProcessor::new(&db);
+    assert_eq!(
+        other_processor.get_management_tel_state(&registry_id)?,
+        processor.get_management_tel_state(&registry_id)?
+    );
+    revoke(&other_processor, &registry_id, "some message")?;
+    assert_eq!(other_processor.get_vc_state(&vc_id)?, TelState::Revoked);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_tip_digests() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let (_other_root, other_db) = test_db();
+    let other_processor = EventProcessor::new(&other_db);
+
+    // Build the same history in both databases.
+    let issuer = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY";
+    let registry_id = incept_registry(&processor, issuer)?;
+    assert_eq!(incept_registry(&other_processor, issuer)?, registry_id);
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some message".as_bytes());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+    processor.process(VerifiableEvent::new(
+        iss.clone(),
+        dummy_source_seal()?.into(),
+    ))?;
+    other_processor.process(VerifiableEvent::new(iss, dummy_source_seal()?.into()))?;
+    let vc_id = CredentialId::from(vc_hash);
+
+    assert!(processor.vc_tip_digest(&vc_id)?.is_some());
+    assert_eq!(
+        processor.vc_tip_digest(&vc_id)?,
+        other_processor.vc_tip_digest(&vc_id)?
+    );
+    assert_eq!(
+        processor.management_tip_digest(&registry_id)?,
+        other_processor.management_tip_digest(&registry_id)?
+    );
+
+    // Histories diverge.
+    revoke(&processor, &registry_id, "some message")?;
+    assert_ne!(
+        processor.vc_tip_digest(&vc_id)?,
+        other_processor.vc_tip_digest(&vc_id)?
+    );
+
+    let unknown_vc = CredentialId::from(SelfAddressing::Blake3_256.derive("unknown vc".as_bytes()));
+    assert_eq!(processor.vc_tip_digest(&unknown_vc)?, None);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_tip_digests_match_next_event() -> Result<(), Error> {
+    use crate::event::{manager_event::ManagerEventType, vc_event::VCEventType, Event};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let derivation = SelfAddressing::SHA3_256;
+
+    // Registry made with non default derivation.
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        0,
+        vec![],
+        Some(&derivation),
+        None,
+    )?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+    let tip = processor.management_tip_digest(&registry_id)?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let vrt = event_generator::make_rotation_event(&st, &[backer], &[], Some(&derivation), None)?;
+    match vrt {
+        Event::Management(ref man) => match man.event_type {
+            ManagerEventType::Vrt(ref rot) => assert_eq!(Some(rot.prev_event.clone()), tip),
+            _ => panic!("Expected vrt event"),
+        },
+        _ => panic!("Expected management event"),
+    }
+    processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+
+    // Timestamped vc event.
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = derivation.derive("some vc".as_bytes());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), Some(&derivation), None)?;
+    processor.process(VerifiableEvent::new(iss, dummy_source_seal()?.into()))?;
+    let vc_id = CredentialId::from(vc_hash.clone());
+    let tip = processor.vc_tip_digest(&vc_id)?;
+    let last = match processor.get_vc_state(&vc_id)? {
+        TelState::Issued(last) => last,
+        _ => return Err(Error::Generic("Vc not issued".into())),
+    };
+    let rev = event_generator::make_revoke_event(&vc_hash, &last, &st, Some(&derivation), None)?;
+    match rev {
+        Event::Vc(ref vc) => match vc.event.event_type {
+            VCEventType::Brv(ref rev) => assert_eq!(Some(rev.prev_event_hash.clone()), tip),
+            _ => panic!("Expected brv event"),
+        },
+        _ => panic!("Expected vc event"),
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_process_with_content() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+
+    let vc = "some message";
+    let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+    let verifiable_iss = VerifiableEvent::new(iss, dummy_source_seal()?.into());
+
+    // Content of other credential is rejected and event isn't stored.
+    let result = processor.process_with_content(verifiable_iss.clone(), b"other message");
+    assert!(result.is_err());
+    let vc_prefix = CredentialId::from(vc_hash);
+    assert_eq!(processor.get_vc_state(&vc_prefix)?, TelState::NotIsuued);
+
+    processor.process_with_content(verifiable_iss, vc.as_bytes())?;
+    assert!(matches!(
+        processor.get_vc_state(&vc_prefix)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_is_backer() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let first_backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let second_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        1,
+        vec![first_backer.clone()],
+        None,
+        None,
+    )?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+    assert!(processor.is_backer(&registry_id, &first_backer)?);
+    assert!(!processor.is_backer(&registry_id, &second_backer)?);
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vrt = event_generator::make_rotation_event(
+        &st,
+        &[second_backer.clone()],
+        &[first_backer.clone()],
+        None,
+        None,
+    )?;
+    processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+    assert!(processor.is_backer(&registry_id, &second_backer)?);
+    assert!(!processor.is_backer(&registry_id, &first_backer)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_vc_state_at_sn() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_id = issue(&processor, &registry_id, "some message")?;
+    revoke(&processor, &registry_id, "some message")?;
+
+    assert!(matches!(
+        processor.vc_state_at_sn(&vc_id, 0)?,
+        TelState::Issued(_)
+    ));
+    assert_eq!(processor.vc_state_at_sn(&vc_id, 1)?, TelState::Revoked);
+    assert!(matches!(
+        processor.vc_state_at_sn(&vc_id, 2),
+        Err(Error::OutOfOrder {
+            expected: 1,
+            got: 2
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_flush() -> Result<(), Error> {
+    use crate::database::EventDatabase;
+
+    let root = tempfile::Builder::new()
+        .prefix("test-db")
+        .tempdir()
+        .unwrap();
+    let (registry_id, vc_id) = {
+        let db = EventDatabase::new(root.path())?;
+        let processor = EventProcessor::new(&db);
+        let registry_id =
+            incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+        let vc_id = issue(&processor, &registry_id, "some message")?;
+        processor.flush()?;
+        (registry_id, vc_id)
+    };
+
+    // Reopen database from the same path.
+    let db = EventDatabase::new(root.path())?;
+    let processor = EventProcessor::new(&db).with_auto_flush(true);
+    assert_eq!(processor.get_management_tel_state(&registry_id)?.sn, 0);
+    assert!(matches!(
+        processor.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_issuance_in_other_registry() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let first_registry =
+        incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let second_registry =
+        incept_registry(&processor, "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+
+    let vc_id = issue(&processor, &first_registry, "some message")?;
+    assert!(matches!(
+        issue(&processor, &second_registry, "some message"),
+        Err(Error::WrongState(_))
+    ));
+
+    // Vc still belongs to the first registry.
+    assert_eq!(
+        db.get_events(&vc_id.prefix()).map(|events| events.count()),
+        Some(1)
+    );
+    assert_eq!(processor.get_registry_vcs(&first_registry)?, vec![vc_id]);
+    assert!(processor.get_registry_vcs(&second_registry)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_management_history() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let first_backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let second_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let rotations: Vec<(Vec<IdentifierPrefix>, Vec<IdentifierPrefix>)> = vec![
+        (vec![first_backer.clone()], vec![]),
+        (vec![second_backer.clone()], vec![first_backer.clone()]),
+    ];
+    for (ba, br) in rotations {
+        let st = processor.get_management_tel_state(&registry_id)?;
+        let vrt = event_generator::make_rotation_event(&st, &ba, &br, None, None)?;
+        processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+    }
+
+    let history = processor.management_history(&registry_id)?;
+    assert_eq!(history.len(), 3);
+    let sns: Vec<u64> = history.iter().map(|(event, _)| event.sn).collect();
+    assert_eq!(sns, vec![0, 1, 2]);
+    let backers: Vec<_> = history
+        .iter()
+        .map(|(_, state)| state.backers.clone().unwrap())
+        .collect();
+    assert_eq!(backers[0], vec![]);
+    assert_eq!(backers[1], vec![first_backer]);
+    assert_eq!(backers[2], vec![second_backer]);
+    assert_eq!(
+        history.last().map(|(_, state)| state.clone()),
+        Some(processor.get_management_tel_state(&registry_id)?)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_registry_anchor_seals() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let issuance_seal = EventSourceSeal {
+        sn: 2,
+        digest: "EOWdT7a7fZwRz0jiZ0DJxZEM3vsNbLDPEUk-ODnif3O0".parse()?,
+    };
+
+    // Two issuances anchored in the same ixn.
+    let st = processor.get_management_tel_state(&registry_id)?;
+    for vc in &["first vc", "second vc"] {
+        let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes());
+        let iss = event_generator::make_issuance_event(&st, vc_hash, None, None)?;
+        processor.process(VerifiableEvent::new(iss, issuance_seal.clone().into()))?;
+    }
+
+    assert_eq!(
+        processor.registry_anchor_seals(&registry_id)?,
+        vec![dummy_source_seal()?, issuance_seal]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_replayed_rotation() -> Result<(), Error> {
+    use crate::event::{manager_event::ManagerTelEvent, Event};
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let backers = vec!["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?];
+    let vrt = event_generator::make_rotation_event(&st, &backers, &[], None, None)?;
+    processor.process(VerifiableEvent::new(
+        vrt.clone(),
+        dummy_source_seal()?.into(),
+    ))?;
+
+    // Resubmit the same rotation body at sn + 2.
+    let event_type = match vrt {
+        Event::Management(man) => man.event_type,
+        _ => unreachable!(),
+    };
+    let replayed = ManagerTelEvent::new(
+        registry_id.prefix(),
+        3,
+        event_type,
+        SerializationFormats::JSON,
+    )?;
+    let result = processor.process(VerifiableEvent::new(
+        Event::Management(replayed),
+        dummy_source_seal()?.into(),
+    ));
+    assert!(matches!(result, Err(Error::WrongState(_))));
+    assert!(processor
+        .get_escrowed_management_events(&registry_id)?
+        .is_empty());
+    assert_eq!(processor.get_management_tel_state(&registry_id)?.sn, 1);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_strict_mode() -> Result<(), Error> {
+    use crate::event::Event;
+
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let mut tampered_vcp = match vcp {
+        Event::Management(ref man) => man.clone(),
+        _ => unreachable!(),
+    };
+    tampered_vcp.serialization_info.size += 1;
+    let tampered = VerifiableEvent::new(
+        Event::Management(tampered_vcp.clone()),
+        dummy_source_seal()?.into(),
+    );
+
+    let (_strict_root, strict_db) = test_db();
+    let strict_processor = EventProcessor::new(&strict_db);
+    assert!(strict_processor.process(tampered.clone()).is_err());
+    assert!(strict_processor
+        .get_management_event_at_sn(&tampered_vcp.prefix.clone().into(), 0)?
+        .is_none());
+
+    let (_permissive_root, permissive_db) = test_db();
+    let permissive_processor = EventProcessor::new(&permissive_db).strict(false);
+    permissive_processor.process(tampered)?;
+    assert!(permissive_processor
+        .get_management_event_at_sn(&tampered_vcp.prefix.clone().into(), 0)?
+        .is_some());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_issuer_of_vc() -> Result<(), Error> {
+    let issuer = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY";
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, issuer)?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+
+    assert_eq!(processor.issuer_of_vc(&vc_id)?, Some(issuer.parse()?));
+    let unknown_vc = CredentialId::from(SelfAddressing::Blake3_256.derive("unknown vc".as_bytes()));
+    assert_eq!(processor.issuer_of_vc(&unknown_vc)?, None);
+
+    // Vc stored without its registry.
+    let (_other_root, other_db) = test_db();
+    for event in db.get_events(&vc_id.prefix()).into_iter().flatten() {
+        other_db.add_new_event(event, &vc_id.prefix())?;
+    }
+    let other_processor = EventProcessor::new(&other_db);
+    assert!(other_processor.issuer_of_vc(&vc_id).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_concurrent_revocations() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let last = match processor.get_vc_state(&vc_id)? {
+        TelState::Issued(last) => last,
+        _ => unreachable!(),
+    };
+    let revocations = vec![
+        event_generator::make_revoke_event(&vc_hash, &last, &st, None, None)?,
+        event_generator::make_revoke_event(&vc_hash, &last, &st, None, None)?,
+    ];
+
+    let results: Vec<Result<_, Error>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = revocations
+            .into_iter()
+            .map(|rev| {
+                let processor = &processor;
+                scope.spawn(move || {
+                    processor.process(VerifiableEvent::new(rev, dummy_source_seal()?.into()))
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert_eq!(processor.get_vc_state(&vc_id)?, TelState::Revoked);
+    assert_eq!(
+        db.get_events(&vc_id.prefix()).map(|events| events.count()),
+        Some(2)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_is_registry_backerless() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let issuer = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY";
+
+    let backerless_id = incept_registry(&processor, issuer)?;
+    assert!(processor.is_registry_backerless(&backerless_id)?);
+
+    let backed_id = incept_backed_registry(
+        &processor,
+        issuer,
+        &["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?],
+    )?;
+    assert!(!processor.is_registry_backerless(&backed_id)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_vc_state_with_history() -> Result<(), Error> {
+    use crate::{event::vc_event::VCEventType, state::vc_state::TelStateKind};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+    revoke(&processor, &registry_id, "some vc")?;
+
+    // Digests are made the way `p` binds events, without `dt`.
+    let digests = db
+        .get_events(&vc_id.prefix())
+        .into_iter()
+        .flatten()
+        .map(|event| Ok(SelfAddressing::Blake3_256.derive(&event.event.chained_bytes()?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let (state, history) = processor.get_vc_state_with_history(&vc_id)?;
+    assert_eq!(state, TelState::Revoked);
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        (history[0].from, history[0].to),
+        (TelStateKind::NotIssued, TelStateKind::Issued)
+    );
+    assert_eq!(
+        (history[1].from, history[1].to),
+        (TelStateKind::Issued, TelStateKind::Revoked)
+    );
+    assert_eq!(history[0].event_digest, digests[0]);
+    assert_eq!(history[1].event_digest, digests[1]);
+    // Issuance digest is the one revocation points to.
+    match processor
+        .get_revocation_event(&vc_id)?
+        .map(|rev| rev.event_type)
+    {
+        Some(VCEventType::Brv(rev)) => assert_eq!(rev.prev_event_hash, history[0].event_digest),
+        _ => panic!("Expected brv event"),
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_management_sn_position() -> Result<(), Error> {
+    use crate::event::{
+        manager_event::{Inc, ManagerEventType, ManagerTelEvent, Rot},
+        Event,
+    };
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let vcp = Inc {
+        issuer_id: "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        config: vec![],
+        backer_threshold: 0,
+        backers: vec![],
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let registry_id = RegistryId::from(vcp.prefix.clone());
+    let vrt_at = |sn: u64, prev: &ManagerTelEvent| -> Result<ManagerTelEvent, Error> {
+        ManagerTelEvent::new(
+            registry_id.prefix(),
+            sn,
+            ManagerEventType::Vrt(Rot {
+                prev_event: SelfAddressing::Blake3_256.derive(&prev.serialize()?),
+                backers_to_add: vec![],
+                backers_to_remove: vec![],
+                backer_threshold: None,
+            }),
+            SerializationFormats::JSON,
+        )
+    };
+    let import = |event: &ManagerTelEvent| {
+        processor.process(VerifiableEvent::new(
+            Event::Management(event.clone()),
+            dummy_source_seal()?.into(),
+        ))
+    };
+
+    // Inception declaring sn other than 0.
+    let mut misplaced_vcp = vcp.clone();
+    misplaced_vcp.sn = 2;
+    assert!(import(&misplaced_vcp).is_err());
+    assert_eq!(processor.management_event_count(&registry_id)?, 0);
+
+    import(&vcp)?;
+    // Second event declaring sn 5.
+    let skipping_vrt = vrt_at(5, &vcp)?;
+    assert!(matches!(
+        import(&skipping_vrt),
+        Err(Error::OutOfOrder {
+            expected: 1,
+            got: 5
+        })
+    ));
+    // Second event repeating sn of inception.
+    let repeating_vrt = vrt_at(0, &vcp)?;
+    assert!(matches!(import(&repeating_vrt), Err(Error::WrongState(_))));
+    assert_eq!(processor.management_event_count(&registry_id)?, 1);
+
+    import(&vrt_at(1, &vcp)?)?;
+    assert_eq!(processor.management_event_count(&registry_id)?, 2);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_merge_from() -> Result<(), Error> {
+    use crate::processor::MergeReport;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let (_other_root, other_db) = test_db();
+    let other_processor = EventProcessor::new(&other_db);
+
+    // Registry present in both databases.
+    let shared_issuer = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY";
+    let shared_registry = incept_registry(&processor, shared_issuer)?;
+    incept_registry(&other_processor, shared_issuer)?;
+    // Registry present only in other database.
+    let unique_registry = incept_registry(
+        &other_processor,
+        "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM",
+    )?;
+    let vc_id = issue(&other_processor, &unique_registry, "some vc")?;
+
+    let report = processor.merge_from(&other_db)?;
+    assert_eq!(
+        report,
+        MergeReport {
+            merged: 2,
+            duplicates: 1,
+            conflicts: vec![],
+        }
+    );
+    assert_eq!(processor.management_event_count(&shared_registry)?, 1);
+    assert_eq!(processor.management_event_count(&unique_registry)?, 1);
+    assert!(matches!(
+        processor.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_registry_anchor_digest_algorithm() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+
+    // Registry anchor digest computed with other algorithm than registry's.
+    let cross_algorithm_iss = event_generator::make_issuance_event(
+        &st,
+        vc_hash.clone(),
+        Some(&SelfAddressing::SHA3_256),
+        None,
+    )?;
+    let result = processor.process(VerifiableEvent::new(
+        cross_algorithm_iss,
+        dummy_source_seal()?.into(),
+    ));
+    assert!(matches!(
+        result,
+        Err(Error::DigestAlgorithmMismatch {
+            expected: SelfAddressing::Blake3_256,
+            got: SelfAddressing::SHA3_256,
+        })
+    ));
+
+    let iss = event_generator::make_issuance_event(&st, vc_hash, None, None)?;
+    processor.process(VerifiableEvent::new(iss, dummy_source_seal()?.into()))?;
+
+    Ok(())
+}
+
+#[test]
+pub fn test_verify_stream() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let last = match processor.get_vc_state(&vc_id)? {
+        TelState::Issued(last) => last,
+        _ => unreachable!(),
+    };
+    let iss_digest = SelfAddressing::Blake3_256.derive(&last);
+
+    // Revocation not bound to issuance stored in the middle of tel,
+    // followed by proper revocation.
+    let corrupted_rev =
+        event_generator::make_revoke_event(&vc_hash, b"other event", &st, None, None)?;
+    db.add_new_event(
+        VerifiableEvent::new(corrupted_rev, dummy_source_seal()?.into()),
+        &vc_id.prefix(),
+    )?;
+    let rev = event_generator::make_revoke_event(&vc_hash, &last, &st, None, None)?;
+    db.add_new_event(
+        VerifiableEvent::new(rev, dummy_source_seal()?.into()),
+        &vc_id.prefix(),
+    )?;
+
+    let mut stream = processor.verify_stream(&vc_id);
+    let (digest, state) = stream.next().unwrap()?;
+    assert_eq!(digest, iss_digest);
+    assert!(matches!(state, TelState::Issued(_)));
+    assert!(stream.next().unwrap().is_err());
+    assert!(stream.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_issuance_in_registry_without_backers() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let issuer = "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY";
+
+    // Registry expecting backers, but incepted without any.
+    let registry_id = incept_backed_registry(&processor, issuer, &[])?;
+    let result = issue(&processor, &registry_id, "some vc");
+    assert!(matches!(result, Err(Error::WrongState(_))));
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let backers = vec!["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?];
+    let vrt = event_generator::make_rotation_event(&st, &backers, &[], None, None)?;
+    processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+    assert!(matches!(
+        processor.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    // Backerless registry doesn't need backers to issue.
+    let backerless_id =
+        incept_registry(&processor, "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+    let vc_id = issue(&processor, &backerless_id, "other vc")?;
+    assert!(matches!(
+        processor.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_update_source_seal() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db).accept_unanchored(true);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let vc_id = CredentialId::from(vc_hash.clone());
+
+    // Issuance processed before it was anchored in KEL.
+    let iss = event_generator::make_issuance_event(&st, vc_hash, None, None)?;
+    processor.process(VerifiableEvent::unanchored(iss))?;
+    assert_eq!(processor.get_source_seal(&vc_id, 0)?, None);
+
+    let seal = EventSourceSeal {
+        sn: 2,
+        digest: "EOWdT7a7fZwRz0jiZ0DJxZEM3vsNbLDPEUk-ODnif3O0".parse()?,
+    };
+    processor.update_source_seal(&vc_id, 0, seal.clone())?;
+    assert_eq!(processor.get_source_seal(&vc_id, 0)?, Some(seal));
+    assert!(matches!(
+        processor.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    // Attached seal can't be replaced.
+    let result = processor.update_source_seal(&vc_id, 0, dummy_source_seal()?);
+    assert!(matches!(result, Err(Error::WrongState(_))));
+    assert!(processor
+        .update_source_seal(&vc_id, 1, dummy_source_seal()?)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_events_in_kel_window() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let seal_at = |sn| -> Result<EventSourceSeal, Error> {
+        Ok(EventSourceSeal {
+            sn,
+            ..dummy_source_seal()?
+        })
+    };
+    // Registry is incepted at KEL sn 1.
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+    let iss = VerifiableEvent::new(iss, seal_at(2)?.into());
+    processor.process(iss.clone())?;
+
+    let last = match processor.get_vc_state(&CredentialId::from(vc_hash.clone()))? {
+        TelState::Issued(last) => last,
+        _ => return Err(Error::Generic("Vc not issued".into())),
+    };
+    let rev = event_generator::make_revoke_event(&vc_hash, &last, &st, None, None)?;
+    let rev = VerifiableEvent::new(rev, seal_at(3)?.into());
+    processor.process(rev.clone())?;
+
+    assert_eq!(
+        processor.events_in_kel_window(&registry_id, 2, 3)?,
+        vec![iss, rev]
+    );
+    assert_eq!(processor.events_in_kel_window(&registry_id, 1, 3)?.len(), 3);
+    assert!(processor
+        .events_in_kel_window(&registry_id, 4, 10)?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_accept_unanchored() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let vc_id = CredentialId::from(vc_hash.clone());
+    let iss = event_generator::make_issuance_event(&st, vc_hash, None, None)?;
+
+    // Verifier rejects unanchored event.
+    assert!(processor
+        .process(VerifiableEvent::unanchored(iss.clone()))
+        .is_err());
+    assert_eq!(processor.get_vc_state(&vc_id)?, TelState::NotIsuued);
+
+    // Local processor accepts it and stores it as waiting for anchor.
+    let permissive = EventProcessor::new(&db).accept_unanchored(true);
+    let placeholder = EventSourceSeal {
+        sn: 1,
+        digest: Default::default(),
+    };
+    permissive.process(VerifiableEvent::new(iss, placeholder.into()))?;
+    assert_eq!(permissive.get_source_seal(&vc_id, 0)?, None);
+    assert!(matches!(
+        permissive.get_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+    assert_eq!(permissive.anchored_vc_state(&vc_id)?, TelState::NotIsuued);
+
+    permissive.update_source_seal(&vc_id, 0, dummy_source_seal()?)?;
+    assert!(matches!(
+        permissive.anchored_vc_state(&vc_id)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_backer_changelog() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let first_backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let second_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    assert!(processor.backer_changelog(&registry_id)?.is_empty());
+
+    let rotations: Vec<(Vec<IdentifierPrefix>, Vec<IdentifierPrefix>)> = vec![
+        (vec![first_backer.clone(), second_backer.clone()], vec![]),
+        (vec![], vec![first_backer.clone()]),
+    ];
+    for (ba, br) in rotations {
+        let st = processor.get_management_tel_state(&registry_id)?;
+        let vrt = event_generator::make_rotation_event(&st, &ba, &br, None, None)?;
+        processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+    }
+
+    assert_eq!(
+        processor.backer_changelog(&registry_id)?,
+        vec![
+            (1, vec![first_backer.clone(), second_backer], vec![]),
+            (2, vec![], vec![first_backer]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_find_vc_event() -> Result<(), Error> {
+    use crate::event::vc_event::VCEventType;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+    let is_revocation = |event: &crate::event::vc_event::VCEvent| {
+        matches!(event.event_type, VCEventType::Rev(_) | VCEventType::Brv(_))
+    };
+    assert_eq!(processor.find_vc_event(&vc_id, is_revocation)?, None);
+
+    revoke(&processor, &registry_id, "some vc")?;
+    let (sn, rev) = processor
+        .find_vc_event(&vc_id, is_revocation)?
+        .ok_or_else(|| Error::Generic("Revocation not found".into()))?;
+    assert_eq!(sn, 1);
+    assert_eq!(Some(rev), processor.get_revocation_event(&vc_id)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_orphaned_vcs() -> Result<(), Error> {
+    use crate::{event::Event, state::ManagerTelState};
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![Config::NoBackers],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let st = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+
+    // Issuance synced before its registry.
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let vc_id = CredentialId::from(vc_hash.clone());
+    let iss = event_generator::make_issuance_event(&st, vc_hash, None, None)?;
+    db.add_new_event(
+        VerifiableEvent::new(iss, dummy_source_seal()?.into()),
+        &vc_id.prefix(),
+    )?;
+    assert_eq!(processor.orphaned_vcs()?, vec![vc_id]);
+
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+    assert!(processor.orphaned_vcs()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_recover_from_wal() -> Result<(), Error> {
+    let (root, db) = test_db();
+    let wal_path = root.path().join("tel.wal");
+    let processor = EventProcessor::new(&db).with_wal(&wal_path)?;
+    let registry_id = incept_registry(&processor, "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY")?;
+    let vc_id = issue(&processor, &registry_id, "some vc")?;
+    let registry_state = processor.get_management_tel_state(&registry_id)?;
+    let vc_state = processor.get_vc_state(&vc_id)?;
+
+    // Crash before flush loses all events stored in database.
+    let (_recovered_root, recovered_db) = test_db();
+    assert_eq!(
+        EventProcessor::recover_from_wal(&recovered_db, &wal_path)?,
+        2
+    );
+    let recovered = EventProcessor::new(&recovered_db);
+    assert_eq!(
+        recovered.get_management_tel_state(&registry_id)?,
+        registry_state
+    );
+    assert_eq!(recovered.get_vc_state(&vc_id)?, vc_state);
+
+    // Recovery clears the log.
+    assert_eq!(
+        EventProcessor::recover_from_wal(&recovered_db, &wal_path)?,
+        0
+    );
+
+    // Events already stored in database aren't replayed.
+    issue(&processor, &registry_id, "other vc")?;
+    assert_eq!(EventProcessor::recover_from_wal(&db, &wal_path)?, 0);
+
+    // Flush clears the log.
+    issue(&processor, &registry_id, "third vc")?;
+    assert!(std::fs::metadata(&wal_path)?.len() > 0);
+    processor.flush()?;
+    assert_eq!(std::fs::metadata(&wal_path)?.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_threshold_at_sn() -> Result<(), Error> {
+    use crate::event::{
+        manager_event::{ManagerEventType, ManagerTelEvent, Rot},
+        Event,
+    };
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let first_backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let second_backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        1,
+        vec![first_backer],
+        None,
+        None,
+    )?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vrt = Event::Management(ManagerTelEvent::new(
+        registry_id.prefix(),
+        1,
+        ManagerEventType::Vrt(Rot {
+            prev_event: SelfAddressing::Blake3_256.derive(&st.last),
+            backers_to_add: vec![second_backer],
+            backers_to_remove: vec![],
+            backer_threshold: Some(2),
+        }),
+        SerializationFormats::JSON,
+    )?);
+    processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+
+    assert_eq!(processor.threshold_at_sn(&registry_id, 0)?, 1);
+    assert_eq!(processor.threshold_at_sn(&registry_id, 1)?, 2);
+    assert!(processor.threshold_at_sn(&registry_id, 2).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_checkpoint_management() -> Result<(), Error> {
+    use crate::state::ManagerTelState;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &[],
+    )?;
+    let rotate = |ba: &[IdentifierPrefix], br: &[IdentifierPrefix]| -> Result<(), Error> {
+        let st = processor.get_management_tel_state(&registry_id)?;
+        let vrt = event_generator::make_rotation_event(&st, ba, br, None, None)?;
+        processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+        Ok(())
+    };
+    // Fold all events, ignoring checkpoint.
+    let folded = || -> Result<ManagerTelState, Error> {
+        Ok(processor
+            .management_history(&registry_id)?
+            .last()
+            .map(|(_, state)| state.clone())
+            .unwrap_or_default())
+    };
+
+    rotate(&[backer.clone()], &[])?;
+    let before = processor.get_management_tel_state(&registry_id)?;
+    processor.checkpoint_management(&registry_id)?;
+    assert_eq!(
+        db.get_management_checkpoint(registry_id.prefix())?,
+        Some(before.clone())
+    );
+    assert_eq!(processor.get_management_tel_state(&registry_id)?, before);
+
+    // Following rotations are folded from checkpoint.
+    rotate(&[], &[backer])?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    assert_eq!(st.sn, 2);
+    assert_eq!(st, folded()?);
+
+    // Checkpoint which doesn't match stored event is dropped.
+    let stale = ManagerTelState {
+        last: b"rewritten event".to_vec(),
+        ..before
+    };
+    db.set_management_checkpoint(&stale, registry_id.prefix())?;
+    assert_eq!(processor.get_management_tel_state(&registry_id)?, folded()?);
+    assert_eq!(db.get_management_checkpoint(registry_id.prefix())?, None);
+
+    assert!(processor
+        .checkpoint_management(&RegistryId::from(
+            "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse::<IdentifierPrefix>()?
+        ))
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_vcs_anchored_in_management_range() -> Result<(), Error> {
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let registry_id = incept_backed_registry(
+        &processor,
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY",
+        &["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?],
+    )?;
+    let first_vc = issue(&processor, &registry_id, "first vc")?;
+
+    let st = processor.get_management_tel_state(&registry_id)?;
+    let vrt = event_generator::make_rotation_event(
+        &st,
+        &["DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?],
+        &[],
+        None,
+        None,
+    )?;
+    processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into()))?;
+    let second_vc = issue(&processor, &registry_id, "second vc")?;
+
+    assert_eq!(
+        processor.vcs_anchored_in_management_range(&registry_id, 1, 1)?,
+        vec![second_vc.clone()]
+    );
+    assert_eq!(
+        processor.vcs_anchored_in_management_range(&registry_id, 0, 0)?,
+        vec![first_vc]
+    );
+    assert_eq!(
+        processor
+            .vcs_anchored_in_management_range(&registry_id, 0, 1)?
+            .len(),
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_blocklist() -> Result<(), Error> {
+    use keri::prefix::Prefix;
+
+    let blocked_issuer: IdentifierPrefix =
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?;
+    let blocked_backer: IdentifierPrefix =
+        "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db).with_blocklist(
+        vec![blocked_issuer.clone(), blocked_backer.clone()]
+            .into_iter()
+            .collect(),
+    );
+
+    let result = incept_registry(&processor, &blocked_issuer.to_str());
+    assert!(matches!(result, Err(Error::Blocked(id)) if id == blocked_issuer));
+
+    let registry_id = incept_backed_registry(
+        &processor,
+        "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM",
+        &[],
+    )?;
+    let st = processor.get_management_tel_state(&registry_id)?;
+    assert_eq!(st.sn, 0);
+
+    // Blocked backer can't be added by rotation.
+    let vrt =
+        event_generator::make_rotation_event(&st, &[blocked_backer.clone()], &[], None, None)?;
+    assert!(matches!(
+        processor.process(VerifiableEvent::new(vrt, dummy_source_seal()?.into())),
+        Err(Error::Blocked(id)) if id == blocked_backer
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_verify_registry_anchoring() -> Result<(), Error> {
+    use crate::{event::Event, state::ManagerTelState};
+
+    let issuer = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM";
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let vcp = event_generator::make_inception_event(
+        issuer.parse()?,
+        vec![Config::NoBackers],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    let st = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => unreachable!(),
+    };
+    let issue_vc = |vc: &str| {
+        let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes());
+        event_generator::make_issuance_event(&st, vc_hash, None, None)
+    };
+    let anchored_iss = issue_vc("anchored vc")?;
+    let tampered_iss = issue_vc("tampered vc")?;
+
+    // Issuer KEL, which seals inception and both issuances.
+    let kel = vec![
+        make_ixn(issuer, 0, &[])?,
+        make_ixn(issuer, 1, &[&vcp])?,
+        make_ixn(issuer, 2, &[&anchored_iss, &tampered_iss])?,
+    ];
+    processor.process(VerifiableEvent::new(vcp, kel_source_seal(&kel[1])?))?;
+    processor.process(VerifiableEvent::new(
+        anchored_iss,
+        kel_source_seal(&kel[2])?,
+    ))?;
+    assert!(processor
+        .registry_anchoring_failures(&registry_id, &kel)?
+        .is_empty());
+
+    // Source seal of the other issuance points to sealing KEL event, but its
+    // digest is tampered.
+    let tampered_digest = SelfAddressing::Blake3_256.derive(&tampered_iss.chained_bytes()?);
+    let tampered_seal = AttachedSourceSeal::new(2, SelfAddressing::Blake3_256.derive(b"tampered"));
+    processor.process(VerifiableEvent::new(tampered_iss, tampered_seal))?;
+    let failures = processor.registry_anchoring_failures(&registry_id, &kel)?;
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, tampered_digest);
+    assert!(matches!(failures[0].1, Error::Generic(_)));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_process_anchored_rotation() -> Result<(), Error> {
+    use crate::{event::Event, state::ManagerTelState};
+
+    let issuer = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM";
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+    let vcp =
+        event_generator::make_inception_event(issuer.parse()?, vec![], 0, vec![], None, None)?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    let st = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => unreachable!(),
+    };
+    let backer: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let vrt = event_generator::make_rotation_event(&st, &[backer], &[], None, None)?;
+
+    // Issuer KEL seals inception and then rotation.
+    let kel = vec![
+        make_ixn(issuer, 0, &[])?,
+        make_ixn(issuer, 1, &[&vcp])?,
+        make_ixn(issuer, 2, &[&vrt])?,
+    ];
+    processor.process_anchored(VerifiableEvent::new(vcp, kel_source_seal(&kel[1])?), &kel)?;
+
+    // Rotation with source seal pointing to KEL event which doesn't seal it
+    // is rejected.
+    let misanchored = VerifiableEvent::new(vrt.clone(), kel_source_seal(&kel[1])?);
+    assert!(processor.process_anchored(misanchored, &kel).is_err());
+    assert_eq!(processor.management_event_count(&registry_id)?, 1);
+
+    processor.process_anchored(VerifiableEvent::new(vrt, kel_source_seal(&kel[2])?), &kel)?;
+    assert_eq!(processor.get_management_tel_state(&registry_id)?.sn, 1);
+    assert!(processor
+        .registry_anchoring_failures(&registry_id, &kel)?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_inception_threshold() -> Result<(), Error> {
+    use crate::event::{manager_event::Inc, Event};
+    use keri::event::SerializationFormats;
+
+    let (_root, db) = test_db();
+    let processor = EventProcessor::new(&db);
+
+    // Inception requiring receipts of more backers than it designates.
+    let vcp = Inc {
+        issuer_id: "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        config: vec![],
+        backer_threshold: 2,
+        backers: vec!["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?],
+        next_backers_digest: None,
+    }
+    .incept_self_addressing(&SelfAddressing::Blake3_256, SerializationFormats::JSON)?;
+    let registry_id = RegistryId::from(vcp.prefix.clone());
+    let result = processor.process(VerifiableEvent::new(
+        Event::Management(vcp),
+        dummy_source_seal()?.into(),
+    ));
+    assert!(matches!(
+        result,
+        Err(Error::InvalidThreshold {
+            threshold: 2,
+            backers: 1
+        })
+    ));
+    assert_eq!(processor.management_event_count(&registry_id)?, 0);
+
+    Ok(())
+}
@@ -1,8 +1,19 @@
 use base64::URL_SAFE;
-use keri::prefix::{Prefix, SelfAddressingPrefix};
+use keri::{
+    derivation::self_addressing::SelfAddressing,
+    event::{
+        event_data::EventData,
+        sections::seal::{EventSeal, Seal},
+    },
+    event_message::EventMessage,
+    prefix::{IdentifierPrefix, Prefix, SelfAddressingPrefix},
+};
 use serde::{Deserialize, Serialize};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    event::{verifiable_event::VerifiableEvent, Event},
+};
 
 pub mod parse;
 
@@ -17,6 +28,33 @@ pub struct AttachedSourceSeal {
     pub seal: EventSourceSeal,
 }
 
+impl EventSourceSeal {
+    /// Checks if seal is well formed: its digest is made with supported
+    /// algorithm and isn't a placeholder. Any sn is valid, because attached
+    /// seal encodes sn on 16 bytes.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.digest.derivation {
+            SelfAddressing::Blake3_256
+            | SelfAddressing::Blake2B256(_)
+            | SelfAddressing::Blake2S256(_)
+            | SelfAddressing::SHA3_256
+            | SelfAddressing::SHA2_256 => (),
+            ref derivation => return Err(Error::UnsupportedDigest(derivation.clone())),
+        };
+        if self.is_placeholder() {
+            return Err(Error::Generic("Improper source seal digest".into()));
+        }
+        Ok(())
+    }
+
+    /// Checks if seal has empty or zeroed digest, so it doesn't point to any
+    /// real KEL event.
+    pub fn is_placeholder(&self) -> bool {
+        let digest = self.digest.derivative();
+        digest.is_empty() || digest.iter().all(|byte| *byte == 0)
+    }
+}
+
 impl From<EventSourceSeal> for AttachedSourceSeal {
     fn from(seal: EventSourceSeal) -> Self {
         AttachedSourceSeal { seal }
@@ -40,8 +78,238 @@ impl AttachedSourceSeal {
     }
 }
 
-fn num_to_base_64(sn: u64) -> Result<String, Error> {
+/// Returns seals of TEL events anchored in KEL event. Digest seals and seals
+/// of events with not self-addressing prefix, which can't be TEL events, are
+/// skipped.
+pub fn extract_tel_seals(event: &EventMessage) -> Vec<EventSeal> {
+    let seals = match event.event.event_data {
+        EventData::Icp(ref icp) => &icp.data,
+        EventData::Rot(ref rot) => &rot.data,
+        EventData::Ixn(ref ixn) => &ixn.data,
+        _ => return vec![],
+    };
+    seals
+        .iter()
+        .filter_map(|seal| match seal {
+            Seal::Event(seal) => match seal.prefix {
+                IdentifierPrefix::SelfAddressing(_) => Some(seal.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Looks for KEL event which seals given TEL event and returns source seal
+/// pointing to it. `None` if none of events anchors TEL event. Seal may bind
+/// the whole event or, as `p` does, vc event without `dt`, so anchor is found
+/// regardless of timestamp. Events are hashed with digest algorithm of the
+/// seal, which is used for digest of anchoring KEL event too.
+pub fn find_anchor(
+    tel_event: &Event,
+    kel_events: &[EventMessage],
+) -> Result<Option<EventSourceSeal>, Error> {
+    let tel_prefix = tel_event.get_prefix();
+    let serialized = tel_event.serialize()?;
+    let chained = tel_event.chained_bytes()?;
+    for kel_event in kel_events {
+        let anchoring_seal = extract_tel_seals(kel_event).into_iter().find(|seal| {
+            let derivation = &seal.event_digest.derivation;
+            seal.prefix == tel_prefix
+                && seal.sn == tel_event.get_sn()
+                && (derivation.derive(&serialized) == seal.event_digest
+                    || derivation.derive(&chained) == seal.event_digest)
+        });
+        if let Some(seal) = anchoring_seal {
+            return Ok(Some(EventSourceSeal {
+                sn: kel_event.event.sn,
+                digest: seal.event_digest.derivation.derive(&kel_event.serialize()?),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Checks if source seal of TEL event points to KEL event which seals it.
+/// KEL events are indexed by their sn.
+pub fn verify_anchor(event: &VerifiableEvent, kel_events: &[EventMessage]) -> Result<(), Error> {
+    let seal = match event.seal {
+        Some(ref seal) if !seal.seal.is_placeholder() => &seal.seal,
+        _ => return Err(Error::MissingAnchor),
+    };
+    let kel_event = kel_events
+        .get(seal.sn as usize)
+        .ok_or_else(|| Error::Generic(format!("No KEL event of sn {}", seal.sn)))?;
+    if !seal.digest.verify_binding(&kel_event.serialize()?) {
+        return Err(Error::Generic("Source seal doesn't match KEL event".into()));
+    }
+    match find_anchor(&event.event, std::slice::from_ref(kel_event))? {
+        Some(_) => Ok(()),
+        None => Err(Error::Generic("KEL event doesn't seal TEL event".into())),
+    }
+}
+
+pub fn num_to_base_64(sn: u64) -> Result<String, Error> {
     let mut tmp = vec![0, 0, 0, 0, 0, 0, 0, 0];
     tmp.extend(u64::to_be_bytes(sn).to_vec());
     Ok((&base64::encode_config(tmp, URL_SAFE)[..22]).to_string())
 }
+
+/// Decodes sn encoded by `num_to_base_64`.
+pub fn base_64_to_num(b64: &str) -> Result<u64, Error> {
+    if b64.len() != 22 {
+        return Err(Error::Generic(format!(
+            "Improper base64 sn length: expected 22, got {}",
+            b64.len()
+        )));
+    }
+    let decoded = base64::decode_config(b64, URL_SAFE)
+        .map_err(|e| Error::Generic(format!("Improper base64 sn: {}", e)))?;
+    if decoded.len() != 16 {
+        return Err(Error::Generic(format!(
+            "Improper decoded sn length: expected 16, got {}",
+            decoded.len()
+        )));
+    }
+    let (padding, sn) = decoded.split_at(8);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(Error::Generic("Sn doesn't fit in u64".into()));
+    }
+    let mut sn_array: [u8; 8] = [0; 8];
+    sn_array.copy_from_slice(sn);
+    Ok(u64::from_be_bytes(sn_array))
+}
+
+#[test]
+fn test_base_64_sn_roundtrip() -> Result<(), Error> {
+    let sns = (0..1000)
+        .chain((0..64).map(|shift| 1u64 << shift))
+        .chain(vec![u64::MAX - 1, u64::MAX]);
+    for sn in sns {
+        assert_eq!(base_64_to_num(&num_to_base_64(sn)?)?, sn);
+    }
+
+    assert_eq!(base_64_to_num("AAAAAAAAAAAAAAAAAAAABw")?, 7);
+    assert!(base_64_to_num("AAAAAAAAAAAAAAAAAAAAB").is_err());
+    assert!(base_64_to_num("AAAAAAAAAAAAAAAAAAAA!B").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_extract_tel_seals() -> Result<(), Error> {
+    let ixn_raw = r#"{"v":"KERI10JSON0001ac_","i":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"2","t":"ixn","p":"EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8","a":[{"i":"EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs","s":"0","d":"Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4"},{"d":"EAw68wa_F60wtPJ8MPsz7UOv9wRMI6Yi5aeJjKL2ijHs"},{"i":"DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU","s":"1","d":"EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw"}]}"#;
+    let ixn: EventMessage = serde_json::from_str(ixn_raw)?;
+
+    let expected = EventSeal {
+        prefix: "EjD_sFljMHXJCC3rEFL93MwHNGguKdC11mcMuQnZitcs".parse()?,
+        sn: 0,
+        event_digest: "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?,
+    };
+    assert_eq!(extract_tel_seals(&ixn), vec![expected]);
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_seal() -> Result<(), Error> {
+    let seal = EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    };
+    assert!(seal.validate().is_ok());
+
+    let unsupported = EventSourceSeal {
+        sn: 1,
+        digest: SelfAddressing::SHA2_512.derive(b"kel event"),
+    };
+    assert!(matches!(
+        unsupported.validate(),
+        Err(Error::UnsupportedDigest(SelfAddressing::SHA2_512))
+    ));
+
+    let placeholder = EventSourceSeal {
+        sn: 1,
+        digest: SelfAddressingPrefix::default(),
+    };
+    assert!(placeholder.validate().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_find_anchor() -> Result<(), Error> {
+    use crate::{
+        event::vc_event::TimestampedVCEvent, state::ManagerTelState, tel::event_generator,
+    };
+
+    let vcp = event_generator::make_inception_event(
+        "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        vec![],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let st = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    let issue = |vc: &str| {
+        event_generator::make_issuance_event(
+            &st,
+            SelfAddressing::Blake3_256.derive(vc.as_bytes()),
+            None,
+            None,
+        )
+    };
+    let anchored_iss = issue("anchored vc")?;
+    let unanchored_iss = issue("unanchored vc")?;
+
+    // Issuer KEL: ixn without seals and ixn sealing one issuance.
+    let make_ixn = |sn: u64, seals: Vec<EventSeal>| -> Result<EventMessage, Error> {
+        let ixn_raw = format!(
+            r#"{{"v":"KERI10JSON000000_","i":"DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM","s":"{:x}","t":"ixn","p":"EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8","a":{}}}"#,
+            sn,
+            serde_json::to_string(&seals)?
+        );
+        Ok(serde_json::from_str(&ixn_raw)?)
+    };
+    let seal = EventSeal {
+        prefix: anchored_iss.get_prefix(),
+        sn: 0,
+        event_digest: SelfAddressing::Blake3_256.derive(&anchored_iss.serialize()?),
+    };
+    let kel = vec![make_ixn(1, vec![])?, make_ixn(2, vec![seal])?];
+
+    let expected = EventSourceSeal {
+        sn: 2,
+        digest: SelfAddressing::Blake3_256.derive(&kel[1].serialize()?),
+    };
+    assert_eq!(find_anchor(&anchored_iss, &kel)?, Some(expected));
+    assert_eq!(find_anchor(&unanchored_iss, &kel)?, None);
+
+    // Seal made with other derivation, binding event without `dt`, is found
+    // for event carrying different timestamp.
+    let restamped_iss = match anchored_iss {
+        Event::Vc(ref vc) => Event::Vc(TimestampedVCEvent::with_timestamp(
+            vc.event.clone(),
+            vc.timestamp() - chrono::Duration::hours(1),
+        )?),
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    assert_ne!(restamped_iss.serialize()?, anchored_iss.serialize()?);
+    let sha3_seal = EventSeal {
+        prefix: anchored_iss.get_prefix(),
+        sn: 0,
+        event_digest: SelfAddressing::SHA3_256.derive(&anchored_iss.chained_bytes()?),
+    };
+    let kel = vec![make_ixn(1, vec![sha3_seal])?];
+    let expected = EventSourceSeal {
+        sn: 1,
+        digest: SelfAddressing::SHA3_256.derive(&kel[0].serialize()?),
+    };
+    assert_eq!(find_anchor(&restamped_iss, &kel)?, Some(expected));
+
+    Ok(())
+}
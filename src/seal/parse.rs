@@ -1,8 +1,7 @@
-use base64::URL_SAFE;
-use keri::event_parsing::prefix::self_addressing_prefix;
+use keri::prefix::SelfAddressingPrefix;
 use nom::{bytes::complete::take, error::ErrorKind};
 
-use crate::{error::Error, seal::EventSourceSeal};
+use crate::seal::{base_64_to_num, EventSourceSeal};
 
 fn attached_sn(s: &[u8]) -> nom::IResult<&[u8], u64> {
     let (more, type_c) = take(2u8)(s)?;
@@ -13,8 +12,11 @@ fn attached_sn(s: &[u8]) -> nom::IResult<&[u8], u64> {
         A => {
             let (rest, parsed_sn) = take(22u8)(more)?;
 
-            let sn =
-                base64_to_num(parsed_sn).map_err(|_| nom::Err::Failure((s, ErrorKind::IsNot)))?;
+            let sn = std::str::from_utf8(parsed_sn)
+                .map_err(|_| nom::Err::Failure((s, ErrorKind::IsNot)))
+                .and_then(|b64| {
+                    base_64_to_num(b64).map_err(|_| nom::Err::Failure((s, ErrorKind::IsNot)))
+                })?;
 
             Ok((rest, sn))
         }
@@ -22,11 +24,22 @@ fn attached_sn(s: &[u8]) -> nom::IResult<&[u8], u64> {
     }
 }
 
-fn base64_to_num(b64: &[u8]) -> Result<u64, Error> {
-    let b64decode = base64::decode_config(b64, URL_SAFE).unwrap();
-    let mut sn_array: [u8; 8] = [0; 8];
-    sn_array.copy_from_slice(&b64decode[8..]);
-    Ok(u64::from_be_bytes(sn_array))
+// Parses self addressing digest. Its length is checked before it's decoded,
+// so truncated or non ASCII input results in error.
+fn event_digest(s: &[u8]) -> nom::IResult<&[u8], SelfAddressingPrefix> {
+    // Digests of two character codes are 512 bits long.
+    let len = match s.first() {
+        Some(b'0') => 88usize,
+        Some(_) => 44,
+        None => return Err(nom::Err::Error((s, ErrorKind::Eof))),
+    };
+    let (rest, digest) = take(len)(s)?;
+    let digest = std::str::from_utf8(digest)
+        .ok()
+        .filter(|digest| digest.is_ascii())
+        .and_then(|digest| digest.parse().ok())
+        .ok_or(nom::Err::Failure((s, ErrorKind::IsNot)))?;
+    Ok((rest, digest))
 }
 
 /// extracts the Event source seal
@@ -37,7 +50,7 @@ pub fn event_source_seal(s: &[u8]) -> nom::IResult<&[u8], EventSourceSeal> {
     match type_c {
         A => {
             let (rest, sn) = attached_sn(more)?;
-            let (rest, event_digest) = self_addressing_prefix(rest)?;
+            let (rest, event_digest) = event_digest(rest)?;
             let seal = EventSourceSeal {
                 sn: u64::from(sn),
                 digest: event_digest,
@@ -51,7 +64,6 @@ pub fn event_source_seal(s: &[u8]) -> nom::IResult<&[u8], EventSourceSeal> {
 
 #[test]
 fn test_seal_parse() {
-    use keri::prefix::SelfAddressingPrefix;
     let seal_attachement =
         r#"GAB0AAAAAAAAAAAAAAAAAAAAABwEOWdT7a7fZwRz0jiZ0DJxZEM3vsNbLDPEUk-ODnif3O0"#;
     let seal = event_source_seal(seal_attachement.as_bytes()).unwrap().1;
@@ -60,4 +72,14 @@ fn test_seal_parse() {
         .parse()
         .unwrap();
     assert_eq!(seal.digest, ev_digest);
+
+    // Truncated and non ASCII attachments.
+    for end in 0..seal_attachement.len() {
+        assert!(event_source_seal(&seal_attachement.as_bytes()[..end]).is_err());
+    }
+    let mut corrupted = seal_attachement.as_bytes().to_vec();
+    corrupted[27] = 0xff;
+    assert!(event_source_seal(&corrupted).is_err());
+    corrupted[27] = b'0';
+    assert!(event_source_seal(&corrupted).is_err());
 }
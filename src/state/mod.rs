@@ -1,23 +1,37 @@
 pub mod vc_state;
 
-use keri::prefix::IdentifierPrefix;
+use keri::prefix::{IdentifierPrefix, SelfAddressingPrefix};
+use serde::{Deserialize, Serialize};
 
-use crate::{error::Error, event::manager_event::ManagerTelEvent};
+use crate::{
+    error::Error,
+    event::manager_event::{Config, ManagerTelEvent},
+};
 
 use self::vc_state::TelState;
 
 pub enum State {
     Management(ManagerTelState),
     Tel(TelState),
+    // Event of unknown type was stored, without changing any state.
+    Unknown,
 }
 
-#[derive(Default, PartialEq)]
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct ManagerTelState {
     pub prefix: IdentifierPrefix,
     pub sn: u64,
     pub last: Vec<u8>,
     pub issuer: IdentifierPrefix,
+    // Configuration traits set at inception.
+    pub config: Vec<Config>,
+    // `None` for backerless registry. Empty backer set means registry uses
+    // backers, but has none yet, so it can't issue vcs until first backer is
+    // added by rotation.
     pub backers: Option<Vec<IdentifierPrefix>>,
+    pub backer_policy: BackerPolicy,
+    // Digest of backer set committed for the next rotation, if any.
+    pub next_backers_digest: Option<SelfAddressingPrefix>,
 }
 
 impl ManagerTelState {
@@ -27,4 +41,134 @@ impl ManagerTelState {
     {
         event.apply_to(self)
     }
+
+    /// Encodes state in compact binary form, for caching it without
+    /// folding registry events again.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_cbor::to_vec(self)?)
+    }
+
+    /// Decodes state encoded by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(serde_cbor::from_slice(bytes)?)
+    }
+
+    /// Checks if registry doesn't use backers, so its vc events are issued
+    /// and revoked with `iss` and `rev` events.
+    pub fn is_backerless(&self) -> bool {
+        self.backers.is_none()
+    }
+
+    /// Checks if given identifier is one of current registry backers.
+    pub fn has_backer(&self, prefix: &IdentifierPrefix) -> bool {
+        self.backers
+            .as_ref()
+            .map(|backers| backers.contains(prefix))
+            .unwrap_or(false)
+    }
+
+    /// Checks if receipts made by given backers satisfy registry backer policy.
+    /// Receipts of identifiers that aren't current registry backers are ignored.
+    pub fn is_receipted(&self, receipted_by: &[IdentifierPrefix]) -> bool {
+        let backers = match self.backers {
+            Some(ref backers) => backers,
+            None => return true,
+        };
+        let valid_receipts: Vec<&IdentifierPrefix> = receipted_by
+            .iter()
+            .filter(|backer| backers.contains(backer))
+            .collect();
+        self.backer_policy.is_satisfied(&valid_receipts)
+    }
+}
+
+/// Describes which backers receipts are required to consider event witnessed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BackerPolicy {
+    // Receipts of any `n` backers.
+    Count(u64),
+    // Receipts of all listed backers.
+    Subset(Vec<IdentifierPrefix>),
+}
+
+impl Default for BackerPolicy {
+    fn default() -> Self {
+        BackerPolicy::Count(0)
+    }
+}
+
+impl BackerPolicy {
+    pub fn is_satisfied(&self, receipted_by: &[&IdentifierPrefix]) -> bool {
+        match self {
+            BackerPolicy::Count(threshold) => {
+                let unique = receipted_by
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, backer)| !receipted_by[..*i].contains(*backer))
+                    .count();
+                unique as u64 >= *threshold
+            }
+            BackerPolicy::Subset(required) => {
+                required.iter().all(|backer| receipted_by.contains(&backer))
+            }
+        }
+    }
+
+    /// Checks if policy can be satisfied by given backer set, so threshold
+    /// doesn't exceed number of backers.
+    pub fn validate(&self, backers: &[IdentifierPrefix]) -> Result<(), Error> {
+        match self {
+            BackerPolicy::Count(threshold) if *threshold > backers.len() as u64 => {
+                Err(Error::InvalidThreshold {
+                    threshold: *threshold,
+                    backers: backers.len(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_backer_policy() -> Result<(), Error> {
+    let first: IdentifierPrefix = "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?;
+    let second: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+    let third: IdentifierPrefix = "Dvxo-P4W_Z0xXTfoA3_4DMPn7oi0mLCElOWJDpC0nQXw".parse()?;
+
+    let mut state = ManagerTelState {
+        backers: Some(vec![first.clone(), second.clone(), third.clone()]),
+        backer_policy: BackerPolicy::Count(2),
+        ..ManagerTelState::default()
+    };
+    assert!(state.is_receipted(&[second.clone(), third.clone()]));
+    assert!(!state.is_receipted(&[third.clone()]));
+
+    // Only receipts from required backers count.
+    state.backer_policy = BackerPolicy::Subset(vec![first.clone()]);
+    assert!(!state.is_receipted(&[second.clone(), third.clone()]));
+    assert!(state.is_receipted(&[first, third]));
+
+    Ok(())
+}
+
+#[test]
+fn test_state_bytes_round_trip() -> Result<(), Error> {
+    let state = ManagerTelState {
+        prefix: "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?,
+        sn: 3,
+        last: b"last event".to_vec(),
+        issuer: "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        config: vec![Config::EstablishmentOnly],
+        backers: Some(vec![
+            "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?,
+            "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?,
+        ]),
+        backer_policy: BackerPolicy::Count(2),
+        next_backers_digest: Some("Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?),
+    };
+    let bytes = state.to_bytes()?;
+    assert_eq!(ManagerTelState::from_bytes(&bytes)?, state);
+    assert!(ManagerTelState::from_bytes(b"not a state").is_err());
+
+    Ok(())
 }
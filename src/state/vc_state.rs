@@ -2,6 +2,10 @@ use crate::{
     error::Error,
     event::vc_event::{VCEvent, VCEventType},
 };
+use keri::{
+    event::sections::seal::EventSeal,
+    prefix::{IdentifierPrefix, SelfAddressingPrefix},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -12,12 +16,50 @@ pub enum TelState {
     Revoked,
 }
 
+/// Vc state without its data.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TelStateKind {
+    NotIssued,
+    Issued,
+    Revoked,
+}
+
+/// Vc state change caused by event of given digest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from: TelStateKind,
+    pub to: TelStateKind,
+    pub event_digest: SelfAddressingPrefix,
+}
+
 impl TelState {
+    pub fn kind(&self) -> TelStateKind {
+        match self {
+            TelState::NotIsuued => TelStateKind::NotIssued,
+            TelState::Issued(_) => TelStateKind::Issued,
+            TelState::Revoked => TelStateKind::Revoked,
+        }
+    }
+
+    /// Returns status list bit of vc: `false` if vc is valid, `true` if it
+    /// was revoked. Vc which wasn't issued has no status.
+    pub fn as_status_bit(&self) -> Result<bool, Error> {
+        match self {
+            TelState::Issued(_) => Ok(false),
+            TelState::Revoked => Ok(true),
+            TelState::NotIsuued => Err(Error::WrongState("Vc not issued".into())),
+        }
+    }
+
     pub fn apply(&self, event: &VCEvent) -> Result<Self, Error> {
         match event.event_type.clone() {
-            VCEventType::Bis(_iss) => match self {
+            VCEventType::Bis(iss) => match self {
                 TelState::NotIsuued => {
-                    if event.sn == 0 {
+                    if iss.registry_anchor == EventSeal::default() {
+                        Err(Error::WrongState(
+                            "Issuance isn't anchored in registry".into(),
+                        ))
+                    } else if event.sn == 0 {
                         Ok(TelState::Issued(event.serialize()?))
                     } else {
                         Err(Error::Generic("Wrong sn".into()))
@@ -33,6 +75,7 @@ impl TelState {
                         Err(Error::Generic("Previous event doesn't match".to_string()))
                     }
                 }
+                TelState::NotIsuued => Err(Error::CannotRevokeUnissued),
                 _ => Err(Error::Generic("Wrong state".into())),
             },
             VCEventType::Iss(_iss) => match self {
@@ -47,6 +90,7 @@ impl TelState {
                         Err(Error::Generic("Previous event doesn't match".to_string()))
                     }
                 }
+                TelState::NotIsuued => Err(Error::CannotRevokeUnissued),
                 _ => Err(Error::Generic("Wrong state".into())),
             },
         }
@@ -59,6 +103,61 @@ impl Default for TelState {
     }
 }
 
+/// Folds ordered vc events into vc state, without touching database.
+/// Revoked state is terminal, so events following revocation are ignored.
+/// Repeated issuance identical to the applied one is skipped, while issuance
+/// of different digest results in `Error::ConflictingIssuance`.
+pub fn fold_vc_events(events: &[VCEvent]) -> Result<TelState, Error> {
+    events
+        .iter()
+        .try_fold(TelState::default(), |state, event| match state {
+            TelState::Revoked => Ok(TelState::Revoked),
+            TelState::Issued(ref last) if is_issuance(event) => {
+                if &event.serialize()? == last {
+                    Ok(state)
+                } else {
+                    Err(Error::ConflictingIssuance(event.prefix.clone()))
+                }
+            }
+            state => state.apply(event),
+        })
+}
+
+fn is_issuance(event: &VCEvent) -> bool {
+    matches!(event.event_type, VCEventType::Iss(_) | VCEventType::Bis(_))
+}
+
+/// Returns vcs which state differs between two snapshots, with their old and
+/// new states. Vc missing from a snapshot is considered not issued there.
+pub fn vc_state_diff(
+    old: &[(IdentifierPrefix, TelState)],
+    new: &[(IdentifierPrefix, TelState)],
+) -> Vec<(IdentifierPrefix, TelState, TelState)> {
+    let state_in = |snapshot: &[(IdentifierPrefix, TelState)], id: &IdentifierPrefix| {
+        snapshot
+            .iter()
+            .find(|(vc_id, _)| vc_id == id)
+            .map(|(_, state)| state.clone())
+            .unwrap_or_default()
+    };
+    let changed = new.iter().filter_map(|(id, new_state)| {
+        let old_state = state_in(old, id);
+        if &old_state != new_state {
+            Some((id.clone(), old_state, new_state.clone()))
+        } else {
+            None
+        }
+    });
+    let removed = old.iter().filter_map(|(id, old_state)| {
+        if old_state != &TelState::NotIsuued && !new.iter().any(|(vc_id, _)| vc_id == id) {
+            Some((id.clone(), old_state.clone(), TelState::NotIsuued))
+        } else {
+            None
+        }
+    });
+    changed.chain(removed).collect()
+}
+
 #[test]
 fn test_apply() -> Result<(), Error> {
     use crate::event::vc_event::TimestampedVCEvent;
@@ -88,3 +187,148 @@ fn test_apply() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_vc_state_diff() -> Result<(), Error> {
+    let revoked_vc: IdentifierPrefix = "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?;
+    let new_vc: IdentifierPrefix = "EAw68wa_F60wtPJ8MPsz7UOv9wRMI6Yi5aeJjKL2ijHs".parse()?;
+    let unchanged_vc: IdentifierPrefix = "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?;
+
+    let old = vec![
+        (revoked_vc.clone(), TelState::Issued(vec![0])),
+        (unchanged_vc.clone(), TelState::Issued(vec![1])),
+    ];
+    let new = vec![
+        (revoked_vc.clone(), TelState::Revoked),
+        (unchanged_vc, TelState::Issued(vec![1])),
+        (new_vc.clone(), TelState::Issued(vec![2])),
+    ];
+    assert_eq!(
+        vc_state_diff(&old, &new),
+        vec![
+            (revoked_vc, TelState::Issued(vec![0]), TelState::Revoked),
+            (new_vc, TelState::NotIsuued, TelState::Issued(vec![2])),
+        ]
+    );
+    assert!(vc_state_diff(&new, &new).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_bis_with_default_anchor() -> Result<(), Error> {
+    use crate::event::vc_event::Issuance;
+    use keri::event::SerializationFormats;
+
+    let vc_prefix: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let unanchored_bis = VCEvent::new(
+        vc_prefix.clone(),
+        0,
+        VCEventType::Bis(Issuance::new(EventSeal::default())),
+        SerializationFormats::JSON,
+    )?;
+    assert!(matches!(
+        TelState::default().apply(&unanchored_bis),
+        Err(Error::WrongState(_))
+    ));
+
+    let registry_anchor = EventSeal {
+        prefix: "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?,
+        sn: 3,
+        event_digest: "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?,
+    };
+    let bis = VCEvent::new(
+        vc_prefix,
+        0,
+        VCEventType::Bis(Issuance::new(registry_anchor)),
+        SerializationFormats::JSON,
+    )?;
+    assert!(matches!(
+        TelState::default().apply(&bis)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_fold_vc_events() -> Result<(), Error> {
+    use crate::event::vc_event::{Issuance, Revocation};
+    use keri::{derivation::self_addressing::SelfAddressing, event::SerializationFormats};
+
+    let vc_prefix: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let registry_anchor = EventSeal {
+        prefix: "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?,
+        sn: 3,
+        event_digest: "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?,
+    };
+    let bis = VCEvent::new(
+        vc_prefix.clone(),
+        0,
+        VCEventType::Bis(Issuance::new(registry_anchor)),
+        SerializationFormats::JSON,
+    )?;
+    let revoke = |prev_event: &[u8]| {
+        VCEvent::new(
+            vc_prefix.clone(),
+            1,
+            VCEventType::Brv(Revocation {
+                prev_event_hash: SelfAddressing::Blake3_256.derive(prev_event),
+                registry_anchor: None,
+            }),
+            SerializationFormats::JSON,
+        )
+    };
+
+    let brv = revoke(&bis.serialize()?)?;
+    assert_eq!(fold_vc_events(&[bis.clone(), brv])?, TelState::Revoked);
+
+    let broken_brv = revoke(b"other event")?;
+    assert!(fold_vc_events(&[bis, broken_brv]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_as_status_bit() {
+    assert_eq!(TelState::Issued(vec![]).as_status_bit().ok(), Some(false));
+    assert_eq!(TelState::Revoked.as_status_bit().ok(), Some(true));
+    assert!(TelState::NotIsuued.as_status_bit().is_err());
+}
+
+#[test]
+fn test_fold_duplicated_issuance() -> Result<(), Error> {
+    use crate::event::vc_event::Issuance;
+    use keri::event::SerializationFormats;
+
+    let vc_prefix: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let issue = |anchor_sn| -> Result<VCEvent, Error> {
+        let registry_anchor = EventSeal {
+            prefix: "EE3Xv6CWwEMpW-99rhPD9IHFCR2LN5ienLVI8yG5faBw".parse()?,
+            sn: anchor_sn,
+            event_digest: "Ezpq06UecHwzy-K9FpNoRxCJp2wIGM9u2Edk-PLMZ1H4".parse()?,
+        };
+        VCEvent::new(
+            vc_prefix.clone(),
+            0,
+            VCEventType::Bis(Issuance::new(registry_anchor)),
+            SerializationFormats::JSON,
+        )
+    };
+    let bis = issue(3)?;
+
+    // Identical issuance stored twice is collapsed.
+    assert_eq!(
+        fold_vc_events(&[bis.clone(), bis.clone()])?,
+        TelState::Issued(bis.serialize()?)
+    );
+
+    // Issuance of different digest conflicts with the applied one.
+    let other_bis = issue(4)?;
+    assert!(matches!(
+        fold_vc_events(&[bis, other_bis]),
+        Err(Error::ConflictingIssuance(prefix)) if prefix == vc_prefix
+    ));
+
+    Ok(())
+}
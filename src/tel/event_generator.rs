@@ -8,10 +8,10 @@ use crate::{
     error::Error,
     event::{
         manager_event::{Config, Inc, ManagerEventType, ManagerTelEvent, Rot},
-        vc_event::{Issuance, Revocation, VCEvent, VCEventType},
+        vc_event::{Issuance, Revocation, TimestampedVCEvent, VCEvent, VCEventType},
         Event,
     },
-    state::ManagerTelState,
+    state::{BackerPolicy, ManagerTelState},
 };
 
 pub fn make_inception_event(
@@ -22,11 +22,15 @@ pub fn make_inception_event(
     derivation: Option<&SelfAddressing>,
     serialization_format: Option<&SerializationFormats>,
 ) -> Result<Event, Error> {
+    if !Config::is_backerless(&config) {
+        BackerPolicy::Count(backer_threshold).validate(&backers)?;
+    }
     let event_type = Inc {
         issuer_id: issuer_prefix,
         config,
         backer_threshold,
         backers,
+        next_backers_digest: None,
     };
 
     Ok(Event::Management(
@@ -52,7 +56,13 @@ pub fn make_rotation_event(
             .derive(&state.last),
         backers_to_add: ba.to_vec(),
         backers_to_remove: br.to_vec(),
+        backer_threshold: None,
     };
+    if let Some(ref backers) = state.backers {
+        state
+            .backer_policy
+            .validate(&rot_data.rotate_backers(backers))?;
+    }
     Ok(Event::Management(ManagerTelEvent::new(
         &state.prefix,
         state.sn + 1,
@@ -78,14 +88,14 @@ pub fn make_issuance_event(
     };
     let iss = VCEventType::Bis(Issuance::new(registry_anchor));
     let vc_prefix = IdentifierPrefix::SelfAddressing(vc_hash);
-    Ok(Event::Vc(VCEvent::new(
+    Ok(Event::Vc(TimestampedVCEvent::new(VCEvent::new(
         vc_prefix.clone(),
         0,
         iss,
         serialization_format
             .unwrap_or(&SerializationFormats::JSON)
             .to_owned(),
-    )?))
+    )?)?))
 }
 
 pub fn make_revoke_event(
@@ -109,12 +119,111 @@ pub fn make_revoke_event(
         registry_anchor: Some(registry_anchor),
     });
     let vc_prefix = IdentifierPrefix::SelfAddressing(vc_hash.to_owned());
-    Ok(Event::Vc(VCEvent::new(
+    Ok(Event::Vc(TimestampedVCEvent::new(VCEvent::new(
         vc_prefix,
         1,
         rev,
         serialization_format
             .unwrap_or(&SerializationFormats::JSON)
             .to_owned(),
-    )?))
+    )?)?))
+}
+
+#[test]
+fn test_inception_threshold() -> Result<(), Error> {
+    let issuer_prefix: IdentifierPrefix = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?;
+    let backers: Vec<IdentifierPrefix> = vec![
+        "BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse()?,
+        "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?,
+    ];
+
+    let vcp = make_inception_event(issuer_prefix.clone(), vec![], 2, vec![], None, None);
+    assert!(matches!(
+        vcp,
+        Err(Error::InvalidThreshold {
+            threshold: 2,
+            backers: 0
+        })
+    ));
+    let vcp = make_inception_event(
+        issuer_prefix.clone(),
+        vec![],
+        3,
+        backers.clone(),
+        None,
+        None,
+    );
+    assert!(vcp.is_err());
+
+    let vcp = make_inception_event(
+        issuer_prefix.clone(),
+        vec![],
+        2,
+        backers.clone(),
+        None,
+        None,
+    )?;
+    let st = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    // Rotation can't leave less backers than threshold.
+    assert!(matches!(
+        make_rotation_event(&st, &[], &backers[..1], None, None),
+        Err(Error::InvalidThreshold {
+            threshold: 2,
+            backers: 1
+        })
+    ));
+    assert!(make_rotation_event(&st, &[], &[], None, None).is_ok());
+    // Threshold is irrelevant for backerless registry.
+    let vcp = make_inception_event(
+        issuer_prefix,
+        vec![Config::NoBackers],
+        2,
+        vec![],
+        None,
+        None,
+    );
+    assert!(vcp.is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_timestamped_issuance() -> Result<(), Error> {
+    use crate::state::vc_state::TelState;
+    use chrono::Local;
+
+    let vcp = make_inception_event(
+        "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM".parse()?,
+        vec![],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let state = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => unreachable!(),
+    };
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let iss = make_issuance_event(&state, vc_hash, None, None)?;
+
+    let vc = match iss {
+        Event::Vc(ref vc) => vc,
+        _ => panic!("Expected vc event"),
+    };
+    assert!((Local::now() - vc.timestamp()).num_seconds().abs() < 60);
+    let serialized = String::from_utf8(iss.serialize()?).unwrap();
+    assert!(serialized.contains(r#""dt":""#));
+    // Declared size covers timestamp.
+    assert_eq!(iss.get_serialization_info().size, serialized.len());
+
+    assert!(matches!(
+        TelState::default().apply(&vc.event)?,
+        TelState::Issued(_)
+    ));
+
+    Ok(())
 }
@@ -1,32 +1,168 @@
 use crate::{
     database::EventDatabase,
     error::Error,
-    event::manager_event::Config,
+    event::manager_event::{Config, ManagerEventType, ManagerTelEvent},
+    event::serializer::{DefaultSerializer, TelSerializer},
     event::verifiable_event::VerifiableEvent,
     event::Event,
+    id::{CredentialId, RegistryId},
     processor::EventProcessor,
     state::{vc_state::TelState, ManagerTelState, State},
 };
 use keri::{
     derivation::self_addressing::SelfAddressing,
-    prefix::{IdentifierPrefix, SelfAddressingPrefix},
+    event::SerializationFormats,
+    prefix::{IdentifierPrefix, Prefix, SelfAddressingPrefix},
 };
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
 
 pub mod event_generator;
 
-pub struct Tel<'d> {
-    pub processor: EventProcessor<'d>,
+/// Describes how vc identifier is computed from vc content.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VcIdScheme {
+    // Digest of vc content.
+    Content,
+    // Digest of registry identifier followed by vc content, so the same
+    // content issued in different registries gets different identifiers.
+    Namespaced,
+}
+
+impl Default for VcIdScheme {
+    fn default() -> Self {
+        VcIdScheme::Content
+    }
+}
+
+impl VcIdScheme {
+    /// Computes identifier of vc of given content, issued in given registry.
+    pub fn vc_id(
+        &self,
+        registry_id: &IdentifierPrefix,
+        derivation: &SelfAddressing,
+        vc: &[u8],
+    ) -> SelfAddressingPrefix {
+        derivation.derive(&self.id_data(registry_id, vc))
+    }
+
+    /// Checks if vc identifier is computed from given content, issued in
+    /// given registry.
+    pub fn verify(
+        &self,
+        vc_id: &SelfAddressingPrefix,
+        registry_id: &IdentifierPrefix,
+        vc: &[u8],
+    ) -> bool {
+        vc_id.verify_binding(&self.id_data(registry_id, vc))
+    }
+
+    // Returns data which digest is vc identifier.
+    fn id_data(&self, registry_id: &IdentifierPrefix, vc: &[u8]) -> Vec<u8> {
+        match self {
+            VcIdScheme::Content => vc.to_vec(),
+            VcIdScheme::Namespaced => [registry_id.to_str().as_bytes(), vc].concat(),
+        }
+    }
+}
+
+/// Options used by `Tel` while generating events.
+#[derive(Debug, Clone)]
+pub struct TelConfig {
+    // Format of events made by `Tel`. Processed events are verified in their
+    // own format, declared in version string.
+    pub serialization_format: SerializationFormats,
+    pub derivation: SelfAddressing,
+    pub vc_id_scheme: VcIdScheme,
+}
+
+impl Default for TelConfig {
+    fn default() -> Self {
+        Self {
+            serialization_format: SerializationFormats::JSON,
+            derivation: SelfAddressing::Blake3_256,
+            vc_id_scheme: VcIdScheme::default(),
+        }
+    }
+}
+
+impl TelConfig {
+    pub fn with_serialization_format(mut self, format: SerializationFormats) -> Self {
+        self.serialization_format = format;
+        self
+    }
+
+    pub fn with_derivation(mut self, derivation: SelfAddressing) -> Self {
+        self.derivation = derivation;
+        self
+    }
+
+    pub fn with_vc_id_scheme(mut self, vc_id_scheme: VcIdScheme) -> Self {
+        self.vc_id_scheme = vc_id_scheme;
+        self
+    }
+}
+
+/// Status of credential, ready to be served by status endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialStatus {
+    pub vc_id: SelfAddressingPrefix,
+    // One of "issued", "revoked" or "not_issued", or "invalid" if stored vc
+    // events can't be applied one after another.
+    pub state: String,
+    pub registry: Option<IdentifierPrefix>,
+    pub last_event_digest: Option<SelfAddressingPrefix>,
+    // Whether vc events could be applied one after another.
+    pub verified: bool,
+}
+
+pub struct Tel<'d, S: TelSerializer = DefaultSerializer> {
+    pub processor: EventProcessor<'d, S>,
     tel_prefix: IdentifierPrefix,
+    config: TelConfig,
+    // Identifiers of registries incepted by events made by this tel.
+    incepted: Mutex<Vec<IdentifierPrefix>>,
 }
 
 impl<'d> Tel<'d> {
     pub fn new(db: &'d EventDatabase) -> Self {
+        Self::with_config(db, TelConfig::default())
+    }
+
+    pub fn with_config(db: &'d EventDatabase, config: TelConfig) -> Self {
+        Self::with_serializer_and_config(db, DefaultSerializer, config)
+    }
+}
+
+impl<'d, S: TelSerializer> Tel<'d, S> {
+    pub fn with_serializer(db: &'d EventDatabase, serializer: S) -> Self {
+        Self::with_serializer_and_config(db, serializer, TelConfig::default())
+    }
+
+    pub fn with_serializer_and_config(
+        db: &'d EventDatabase,
+        serializer: S,
+        config: TelConfig,
+    ) -> Self {
+        let processor = EventProcessor::with_serializer(db, serializer)
+            .with_vc_id_scheme(config.vc_id_scheme.clone());
+        // Tel opened over database of single registry manages that registry.
+        let tel_prefix = match processor.registries().as_slice() {
+            [registry_id] => registry_id.prefix().clone(),
+            _ => IdentifierPrefix::default(),
+        };
         Self {
-            processor: EventProcessor::new(db),
-            tel_prefix: IdentifierPrefix::default(),
+            processor,
+            tel_prefix,
+            config,
+            incepted: Mutex::new(vec![]),
         }
     }
 
+    pub fn config(&self) -> &TelConfig {
+        &self.config
+    }
+
     pub fn make_inception_event(
         &self,
         issuer_prefix: IdentifierPrefix,
@@ -34,14 +170,19 @@ impl<'d> Tel<'d> {
         backer_threshold: u64,
         backers: Vec<IdentifierPrefix>,
     ) -> Result<Event, Error> {
-        event_generator::make_inception_event(
+        let vcp = event_generator::make_inception_event(
             issuer_prefix,
             config,
             backer_threshold,
             backers,
-            None,
-            None,
-        )
+            Some(&self.config.derivation),
+            Some(&self.config.serialization_format),
+        )?;
+        self.incepted
+            .lock()
+            .map_err(|_| Error::Generic("Poisoned lock".into()))?
+            .push(vcp.get_prefix());
+        Ok(vcp)
     }
 
     pub fn make_rotation_event(
@@ -49,7 +190,13 @@ impl<'d> Tel<'d> {
         ba: &[IdentifierPrefix],
         br: &[IdentifierPrefix],
     ) -> Result<Event, Error> {
-        event_generator::make_rotation_event(&self.get_management_tel_state()?, ba, br, None, None)
+        event_generator::make_rotation_event(
+            &self.get_management_tel_state()?,
+            ba,
+            br,
+            Some(&self.config.derivation),
+            Some(&self.config.serialization_format),
+        )
     }
 
     pub fn make_issuance_event(
@@ -57,68 +204,146 @@ impl<'d> Tel<'d> {
         derivation: SelfAddressing,
         vc: &str,
     ) -> Result<Event, Error> {
-        let vc_hash = derivation.derive(vc.as_bytes());
-        event_generator::make_issuance_event(&self.get_management_tel_state()?, vc_hash, None, None)
+        let management_state = self.get_management_tel_state()?;
+        if management_state == ManagerTelState::default() {
+            return Err(Error::WrongState("Registry not incepted".into()));
+        }
+        let vc_id = self.vc_id(&derivation, vc);
+        event_generator::make_issuance_event(
+            &management_state,
+            vc_id.into(),
+            Some(&self.config.derivation),
+            Some(&self.config.serialization_format),
+        )
+    }
+
+    /// Returns identifier of given vc in this tel, computed according to
+    /// configured vc id scheme. It should be used to look up issued vcs.
+    pub fn vc_id(&self, derivation: &SelfAddressing, vc: &str) -> CredentialId {
+        self.config
+            .vc_id_scheme
+            .vc_id(&self.tel_prefix, derivation, vc.as_bytes())
+            .into()
     }
 
-    pub fn make_revoke_event(&self, vc: &SelfAddressingPrefix) -> Result<Event, Error> {
-        let vc_state = self.get_vc_state(vc)?;
-        let last = match vc_state {
-            TelState::Issued(last) => last,
+    /// Makes revocation of vc which issuance is stored in database. Registry
+    /// of issuance is used, so vc can be revoked by tel which didn't process
+    /// the issuance itself.
+    pub fn make_revoke_event(&self, vc: &CredentialId) -> Result<Event, Error> {
+        match self.get_vc_state(vc)? {
+            TelState::Issued(_) => (),
+            TelState::NotIsuued => return Err(Error::CannotRevokeUnissued),
+            TelState::Revoked => return Err(Error::Generic("Inproper vc state".into())),
+        }
+        let vc_events = self.get_tel(vc)?;
+        let last = match vc_events.last() {
+            Some(VerifiableEvent {
+                event: Event::Vc(ref last),
+                ..
+            }) => last.event.serialize()?,
             _ => return Err(Error::Generic("Inproper vc state".into())),
         };
-        event_generator::make_revoke_event(vc, &last, &self.get_management_tel_state()?, None, None)
+        let registry_id = vc_events
+            .first()
+            .and_then(|issuance| match issuance.event {
+                Event::Vc(ref vc) => vc.event.registry_id(),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.tel_prefix.clone());
+        event_generator::make_revoke_event(
+            vc.digest(),
+            &last,
+            &self
+                .processor
+                .get_management_tel_state(&registry_id.into())?,
+            Some(&self.config.derivation),
+            Some(&self.config.serialization_format),
+        )
     }
 
     // Process verifiable event. It doesn't check if source seal is correct. Just add event to tel.
     pub fn process(&mut self, event: VerifiableEvent) -> Result<State, Error> {
+        let is_inception = matches!(
+            event.event,
+            Event::Management(ManagerTelEvent {
+                event_type: ManagerEventType::Vcp(_),
+                ..
+            })
+        );
         let state = self.processor.process(event)?;
-        // If tel prefix is not set yet, set it to first processed management event identifier prefix.
-        if self.tel_prefix == IdentifierPrefix::default() {
-            if let State::Management(ref man) = state {
+        // If tel prefix is not set yet, set it to first processed management
+        // event identifier prefix. Tel manages registry it incepted, so
+        // inception made by this tel sets it too, while inceptions of other
+        // registries don't.
+        if let State::Management(ref man) = state {
+            let incepted_here = is_inception
+                && self
+                    .incepted
+                    .lock()
+                    .map_err(|_| Error::Generic("Poisoned lock".into()))?
+                    .contains(&man.prefix);
+            if self.tel_prefix == IdentifierPrefix::default() || incepted_here {
                 self.tel_prefix = man.prefix.to_owned()
             }
         }
         Ok(state)
     }
 
-    pub fn get_vc_state(&self, vc_hash: &SelfAddressingPrefix) -> Result<TelState, Error> {
-        let vc_prefix = IdentifierPrefix::SelfAddressing(vc_hash.to_owned());
-        self.processor.get_vc_state(&vc_prefix)
+    pub fn get_vc_state(&self, vc_id: &CredentialId) -> Result<TelState, Error> {
+        self.processor.get_vc_state(vc_id)
     }
 
-    pub fn get_tel(&self, vc_hash: &SelfAddressingPrefix) -> Result<Vec<VerifiableEvent>, Error> {
-        self.processor.get_events(vc_hash)
+    /// Returns current status of vc.
+    pub fn status_response(&self, vc_id: &CredentialId) -> Result<CredentialStatus, Error> {
+        let (state, verified) = match self.processor.get_vc_state(vc_id) {
+            Ok(TelState::Issued(_)) => ("issued", true),
+            Ok(TelState::Revoked) => ("revoked", true),
+            Ok(TelState::NotIsuued) => ("not_issued", true),
+            Err(_) => ("invalid", false),
+        };
+        let registry = self
+            .get_tel(vc_id)?
+            .first()
+            .and_then(|issuance| match issuance.event {
+                Event::Vc(ref vc) => vc.event.registry_id(),
+                _ => None,
+            });
+        Ok(CredentialStatus {
+            vc_id: vc_id.digest().to_owned(),
+            state: state.into(),
+            registry,
+            last_event_digest: self.processor.vc_tip_digest(vc_id)?,
+            verified,
+        })
+    }
+
+    pub fn get_tel(&self, vc_id: &CredentialId) -> Result<Vec<VerifiableEvent>, Error> {
+        self.processor.get_events(vc_id)
     }
 
     pub fn get_management_tel_state(&self) -> Result<ManagerTelState, Error> {
-        self.processor.get_management_tel_state(&self.tel_prefix)
+        self.processor
+            .get_management_tel_state(&RegistryId::from(self.tel_prefix.clone()))
     }
 }
 #[cfg(test)]
 mod tests {
-    use std::fs;
-
     use crate::{
-        error::Error, event::verifiable_event::VerifiableEvent, seal::EventSourceSeal,
-        state::State, tel::Tel,
+        error::Error,
+        event::{manager_event::Config, verifiable_event::VerifiableEvent},
+        state::State,
+        tel::Tel,
+        test_utils::{dummy_source_seal, test_db},
     };
 
     #[test]
     pub fn test_management_tel() -> Result<(), Error> {
-        use tempfile::Builder;
-
-        let tel_root = Builder::new().prefix("tel-test-db").tempdir().unwrap();
-        fs::create_dir_all(tel_root.path()).unwrap();
-        let tel_db = crate::database::EventDatabase::new(tel_root.path()).unwrap();
+        let (_tel_root, tel_db) = test_db();
         let issuer_prefix = "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?;
 
         // Create tel
         let mut tel = Tel::new(&tel_db);
-        let dummy_source_seal = EventSourceSeal {
-            sn: 1,
-            digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
-        };
+        let dummy_source_seal = dummy_source_seal()?;
 
         let vcp = tel.make_inception_event(issuer_prefix, vec![], 0, vec![])?;
         let verifiable_vcp = VerifiableEvent::new(vcp.clone(), dummy_source_seal.clone().into());
@@ -136,4 +361,321 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_issuance_before_inception() -> Result<(), Error> {
+        use keri::derivation::self_addressing::SelfAddressing;
+
+        let (_tel_root, tel_db) = test_db();
+        let tel = Tel::new(&tel_db);
+
+        let result = tel.make_issuance_event(SelfAddressing::Blake3_256, "some vc");
+        assert!(matches!(result, Err(Error::WrongState(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_tel_config() -> Result<(), Error> {
+        use crate::tel::TelConfig;
+        use keri::{derivation::self_addressing::SelfAddressing, prefix::IdentifierPrefix};
+
+        let (_tel_root, tel_db) = test_db();
+        let issuer_prefix: IdentifierPrefix =
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?;
+
+        // Default config behaves as positional constructor.
+        let tel = Tel::new(&tel_db);
+        let configured_tel = Tel::with_config(&tel_db, TelConfig::default());
+        assert_eq!(
+            tel.make_inception_event(issuer_prefix.clone(), vec![], 0, vec![])?,
+            configured_tel.make_inception_event(issuer_prefix.clone(), vec![], 0, vec![])?
+        );
+
+        // Non default derivation is used for registry identifier.
+        let mut tel = Tel::with_config(
+            &tel_db,
+            TelConfig::default().with_derivation(SelfAddressing::SHA3_256),
+        );
+        let vcp = tel.make_inception_event(issuer_prefix, vec![Config::NoBackers], 0, vec![])?;
+        match vcp.get_prefix() {
+            IdentifierPrefix::SelfAddressing(sap) => {
+                assert_eq!(sap.derivation, SelfAddressing::SHA3_256)
+            }
+            _ => panic!("Expected self addressing registry identifier"),
+        }
+        let dummy_source_seal = dummy_source_seal()?;
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+        let iss = tel.make_issuance_event(SelfAddressing::Blake3_256, "some vc")?;
+        assert!(matches!(
+            tel.process(VerifiableEvent::new(iss, dummy_source_seal.into()))?,
+            State::Tel(_)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_status_response() -> Result<(), Error> {
+        use keri::{derivation::self_addressing::SelfAddressing, prefix::Prefix};
+
+        let (_tel_root, tel_db) = test_db();
+        let mut tel = Tel::new(&tel_db);
+        let dummy_source_seal = dummy_source_seal()?;
+
+        let vcp = tel.make_inception_event(
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?,
+            vec![Config::NoBackers],
+            0,
+            vec![],
+        )?;
+        let registry_id = vcp.get_prefix();
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+
+        let vc = "some vc";
+        let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes()).into();
+        let iss = tel.make_issuance_event(SelfAddressing::Blake3_256, vc)?;
+        tel.process(VerifiableEvent::new(iss, dummy_source_seal.clone().into()))?;
+
+        let status = tel.status_response(&vc_hash)?;
+        assert!(status.verified);
+        let json: serde_json::Value = serde_json::to_value(&status)?;
+        assert_eq!(json["state"], "issued");
+        assert_eq!(json["registry"], registry_id.to_str());
+        let issued_digest = status.last_event_digest;
+        assert!(issued_digest.is_some());
+
+        let rev = tel.make_revoke_event(&vc_hash)?;
+        tel.process(VerifiableEvent::new(rev, dummy_source_seal.into()))?;
+
+        let status = tel.status_response(&vc_hash)?;
+        let json: serde_json::Value = serde_json::to_value(&status)?;
+        assert_eq!(json["state"], "revoked");
+        assert_eq!(json["registry"], registry_id.to_str());
+        assert_ne!(status.last_event_digest, issued_digest);
+
+        // Stored events which can't be applied, like repeated issuance.
+        let issuance = tel.get_tel(&vc_hash)?[0].clone();
+        tel_db.set_events(vec![issuance.clone(), issuance], &vc_hash.prefix())?;
+        let status = tel.status_response(&vc_hash)?;
+        assert_eq!(status.state, "invalid");
+        assert!(!status.verified);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_namespaced_vc_ids() -> Result<(), Error> {
+        use crate::{
+            state::vc_state::TelState,
+            tel::{TelConfig, VcIdScheme},
+        };
+        use keri::derivation::self_addressing::SelfAddressing;
+
+        let (_tel_root, tel_db) = test_db();
+        let dummy_source_seal = dummy_source_seal()?;
+        let config = TelConfig::default().with_vc_id_scheme(VcIdScheme::Namespaced);
+
+        let issuers = vec![
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc",
+            "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM",
+        ];
+        let mut vc_ids = vec![];
+        for issuer in issuers {
+            let mut tel = Tel::with_config(&tel_db, config.clone());
+            let vcp =
+                tel.make_inception_event(issuer.parse()?, vec![Config::NoBackers], 0, vec![])?;
+            tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+            let iss = tel.make_issuance_event(SelfAddressing::Blake3_256, "some vc")?;
+            tel.process(VerifiableEvent::new(iss, dummy_source_seal.clone().into()))?;
+
+            let vc_id = tel.vc_id(&SelfAddressing::Blake3_256, "some vc");
+            assert!(matches!(tel.get_vc_state(&vc_id)?, TelState::Issued(_)));
+            vc_ids.push(vc_id);
+        }
+        assert_ne!(vc_ids[0], vc_ids[1]);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_namespaced_vc_content() -> Result<(), Error> {
+        use crate::{
+            state::vc_state::TelState,
+            tel::{TelConfig, VcIdScheme},
+        };
+        use keri::derivation::self_addressing::SelfAddressing;
+
+        let (_tel_root, tel_db) = test_db();
+        let dummy_source_seal = dummy_source_seal()?;
+        let config = TelConfig::default().with_vc_id_scheme(VcIdScheme::Namespaced);
+
+        let mut tel = Tel::with_config(&tel_db, config.clone());
+        let vcp = tel.make_inception_event(
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?,
+            vec![Config::NoBackers],
+            0,
+            vec![],
+        )?;
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+        let iss = tel.make_issuance_event(SelfAddressing::Blake3_256, "some vc")?;
+        let iss = VerifiableEvent::new(iss, dummy_source_seal.into());
+
+        // Namespaced id is checked against registry and content.
+        assert!(tel
+            .processor
+            .process_with_content(iss.clone(), b"other vc")
+            .is_err());
+        tel.processor.process_with_content(iss, b"some vc")?;
+
+        // Tel opened later over the same database computes the same id.
+        let fresh_tel = Tel::with_config(&tel_db, config);
+        let vc_id = fresh_tel.vc_id(&SelfAddressing::Blake3_256, "some vc");
+        assert_eq!(vc_id, tel.vc_id(&SelfAddressing::Blake3_256, "some vc"));
+        assert!(matches!(
+            fresh_tel.get_vc_state(&vc_id)?,
+            TelState::Issued(_)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_revoke_with_fresh_tel() -> Result<(), Error> {
+        use crate::state::vc_state::TelState;
+        use keri::derivation::self_addressing::SelfAddressing;
+
+        let (_tel_root, tel_db) = test_db();
+        let dummy_source_seal = dummy_source_seal()?;
+
+        // Issue vc with one tel.
+        let mut tel = Tel::new(&tel_db);
+        let vcp = tel.make_inception_event(
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?,
+            vec![Config::NoBackers],
+            0,
+            vec![],
+        )?;
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+        let iss = tel.make_issuance_event(SelfAddressing::Blake3_256, "some vc")?;
+        tel.process(VerifiableEvent::new(iss, dummy_source_seal.clone().into()))?;
+
+        // Revoke it with tel that only sees the issuance in database.
+        let mut fresh_tel = Tel::new(&tel_db);
+        let vc_hash = fresh_tel.vc_id(&SelfAddressing::Blake3_256, "some vc");
+        let rev = fresh_tel.make_revoke_event(&vc_hash)?;
+        fresh_tel.process(VerifiableEvent::new(rev, dummy_source_seal.into()))?;
+        assert_eq!(fresh_tel.get_vc_state(&vc_hash)?, TelState::Revoked);
+        assert_eq!(tel.get_vc_state(&vc_hash)?, TelState::Revoked);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_revoke_unissued() -> Result<(), Error> {
+        use crate::tel::event_generator;
+        use keri::derivation::self_addressing::SelfAddressing;
+
+        let (_tel_root, tel_db) = test_db();
+        let dummy_source_seal = dummy_source_seal()?;
+        let mut tel = Tel::new(&tel_db);
+        let vcp = tel.make_inception_event(
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?,
+            vec![],
+            0,
+            vec![],
+        )?;
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+
+        let vc_hash = SelfAddressing::Blake3_256.derive("never issued vc".as_bytes());
+        assert!(matches!(
+            tel.make_revoke_event(&vc_hash.clone().into()),
+            Err(Error::CannotRevokeUnissued)
+        ));
+
+        let rev = event_generator::make_revoke_event(
+            &vc_hash,
+            "never issued vc".as_bytes(),
+            &tel.get_management_tel_state()?,
+            None,
+            None,
+        )?;
+        assert!(matches!(
+            tel.process(VerifiableEvent::new(rev, dummy_source_seal.into())),
+            Err(Error::CannotRevokeUnissued)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_cbor_vc_in_json_registry() -> Result<(), Error> {
+        use crate::{state::vc_state::TelState, tel::event_generator};
+        use keri::{derivation::self_addressing::SelfAddressing, event::SerializationFormats};
+
+        let (_tel_root, tel_db) = test_db();
+        let dummy_source_seal = dummy_source_seal()?;
+
+        // Registry made by tel configured to use JSON.
+        let mut tel = Tel::new(&tel_db);
+        let vcp = tel.make_inception_event(
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?,
+            vec![Config::NoBackers],
+            0,
+            vec![],
+        )?;
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+
+        // Issuance received from other party in CBOR.
+        let vc_id = tel.vc_id(&SelfAddressing::Blake3_256, "some vc");
+        let iss = event_generator::make_issuance_event(
+            &tel.get_management_tel_state()?,
+            vc_id.digest().clone(),
+            None,
+            Some(&SerializationFormats::CBOR),
+        )?;
+        assert_eq!(
+            iss.get_serialization_info().kind,
+            SerializationFormats::CBOR
+        );
+        tel.process(VerifiableEvent::new(iss, dummy_source_seal.clone().into()))?;
+        assert!(matches!(tel.get_vc_state(&vc_id)?, TelState::Issued(_)));
+
+        // JSON revocation points to digest of CBOR issuance.
+        let rev = tel.make_revoke_event(&vc_id)?;
+        assert_eq!(
+            rev.get_serialization_info().kind,
+            SerializationFormats::JSON
+        );
+        tel.process(VerifiableEvent::new(rev, dummy_source_seal.into()))?;
+        assert_eq!(tel.get_vc_state(&vc_id)?, TelState::Revoked);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_foreign_inception() -> Result<(), Error> {
+        use crate::test_utils::make_inception;
+
+        let (_tel_root, tel_db) = test_db();
+        let dummy_source_seal = dummy_source_seal()?;
+
+        let mut tel = Tel::new(&tel_db);
+        let vcp = tel.make_inception_event(
+            "DpE03it33djytuVvXhSbZdEw0lx7Xa-olrlUUSH2Ykvc".parse()?,
+            vec![Config::NoBackers],
+            0,
+            vec![],
+        )?;
+        let registry_id = vcp.get_prefix();
+        tel.process(VerifiableEvent::new(vcp, dummy_source_seal.clone().into()))?;
+
+        // Inception of other registry, received from other party, doesn't
+        // change registry managed by tel.
+        let (foreign_vcp, _) = make_inception("DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM")?;
+        tel.process(VerifiableEvent::new(foreign_vcp, dummy_source_seal.into()))?;
+        assert_eq!(tel.get_management_tel_state()?.prefix, registry_id);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,58 @@
+//! Fixtures shared by tests of all modules.
+
+use crate::{
+    database::EventDatabase,
+    error::Error,
+    event::{manager_event::Config, verifiable_event::VerifiableEvent, Event},
+    id::RegistryId,
+    processor::EventProcessor,
+    seal::EventSourceSeal,
+    state::ManagerTelState,
+    tel::event_generator,
+};
+
+// Open database in new temporary directory, which is removed when returned
+// handle is dropped.
+pub fn test_db() -> (tempfile::TempDir, EventDatabase) {
+    let root = tempfile::Builder::new()
+        .prefix("test-db")
+        .tempdir()
+        .unwrap();
+    std::fs::create_dir_all(root.path()).unwrap();
+    let db = EventDatabase::new(root.path()).unwrap();
+    (root, db)
+}
+
+// Source seal pointing to made up KEL event.
+pub fn dummy_source_seal() -> Result<EventSourceSeal, Error> {
+    Ok(EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    })
+}
+
+// Make inception of registry without backers of given issuer, together with
+// state it results in.
+pub fn make_inception(issuer: &str) -> Result<(Event, ManagerTelState), Error> {
+    let vcp = event_generator::make_inception_event(
+        issuer.parse()?,
+        vec![Config::NoBackers],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let state = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    Ok((vcp, state))
+}
+
+// Incept registry of given issuer and return its identifier.
+pub fn incept_registry(processor: &EventProcessor, issuer: &str) -> Result<RegistryId, Error> {
+    let (vcp, _) = make_inception(issuer)?;
+    let registry_id = RegistryId::from(vcp.get_prefix());
+    processor.process(VerifiableEvent::new(vcp, dummy_source_seal()?.into()))?;
+    Ok(registry_id)
+}
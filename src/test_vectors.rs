@@ -0,0 +1,146 @@
+//! Deterministic registry events with their expected states, for testing
+//! crates built on top of teliox.
+
+use chrono::DateTime;
+use keri::derivation::self_addressing::SelfAddressing;
+
+use crate::{
+    error::Error,
+    event::{
+        manager_event::Config, vc_event::TimestampedVCEvent, verifiable_event::VerifiableEvent,
+        Event,
+    },
+    id::{CredentialId, RegistryId},
+    seal::EventSourceSeal,
+    state::{vc_state::TelState, ManagerTelState},
+    tel::event_generator,
+};
+
+/// Issuer of the test registry.
+pub const ISSUER: &str = "DntNTPnDFBnmlO6J44LXCrzZTAmpe-82b7BmQGtL4QhM";
+
+/// Content of vc which stays issued.
+pub const ISSUED_VC: &str = "issued vc";
+
+/// Content of vc which is issued and then revoked.
+pub const REVOKED_VC: &str = "revoked vc";
+
+/// Timestamp of all vc events.
+pub const TIMESTAMP: &str = "2021-01-01T00:00:00+00:00";
+
+pub struct TestVector {
+    pub registry_id: RegistryId,
+    // Events in processing order.
+    pub events: Vec<VerifiableEvent>,
+    // Management state after processing all events.
+    pub registry_state: ManagerTelState,
+    // State of each vc after processing all events.
+    pub vc_states: Vec<(CredentialId, TelState)>,
+}
+
+pub fn source_seal() -> Result<EventSourceSeal, Error> {
+    Ok(EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    })
+}
+
+// Replaces generation time of vc event with `TIMESTAMP`.
+fn with_fixed_timestamp(event: Event) -> Result<Event, Error> {
+    match event {
+        Event::Vc(vc) => {
+            let timestamp = DateTime::parse_from_rfc3339(TIMESTAMP)
+                .map_err(|e| Error::Generic(e.to_string()))?;
+            Ok(Event::Vc(TimestampedVCEvent::with_timestamp(
+                vc.event,
+                DateTime::from(timestamp),
+            )?))
+        }
+        event => Ok(event),
+    }
+}
+
+/// Backerless registry with one issued and one revoked vc.
+pub fn registry_with_vcs() -> Result<TestVector, Error> {
+    let vcp = event_generator::make_inception_event(
+        ISSUER.parse()?,
+        vec![Config::NoBackers],
+        0,
+        vec![],
+        None,
+        None,
+    )?;
+    let registry_state = match vcp {
+        Event::Management(ref man) => ManagerTelState::default().apply(man)?,
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    let mut events = vec![VerifiableEvent::new(vcp, source_seal()?.into())];
+    let mut vc_states = vec![];
+
+    for vc in &[ISSUED_VC, REVOKED_VC] {
+        let vc_hash = SelfAddressing::Blake3_256.derive(vc.as_bytes());
+        let iss = with_fixed_timestamp(event_generator::make_issuance_event(
+            &registry_state,
+            vc_hash.clone(),
+            None,
+            None,
+        )?)?;
+        let issued = match iss {
+            Event::Vc(ref vc) => TelState::Issued(vc.event.serialize()?),
+            _ => return Err(Error::Generic("Improper event type".into())),
+        };
+        vc_states.push((CredentialId::from(vc_hash), issued));
+        events.push(VerifiableEvent::new(iss, source_seal()?.into()));
+    }
+
+    let revoked_hash = SelfAddressing::Blake3_256.derive(REVOKED_VC.as_bytes());
+    let last = match vc_states[1].1 {
+        TelState::Issued(ref last) => last.clone(),
+        _ => return Err(Error::Generic("Inproper vc state".into())),
+    };
+    let rev = with_fixed_timestamp(event_generator::make_revoke_event(
+        &revoked_hash,
+        &last,
+        &registry_state,
+        None,
+        None,
+    )?)?;
+    vc_states[1].1 = TelState::Revoked;
+    events.push(VerifiableEvent::new(rev, source_seal()?.into()));
+
+    Ok(TestVector {
+        registry_id: RegistryId::from(registry_state.prefix.clone()),
+        events,
+        registry_state,
+        vc_states,
+    })
+}
+
+#[test]
+fn test_registry_with_vcs() -> Result<(), Error> {
+    use crate::processor::EventProcessor;
+
+    let root = tempfile::Builder::new()
+        .prefix("test-db")
+        .tempdir()
+        .unwrap();
+    let db = crate::database::EventDatabase::new(root.path())?;
+    let processor = EventProcessor::new(&db);
+
+    let vector = registry_with_vcs()?;
+    // Vectors are deterministic.
+    assert_eq!(vector.events, registry_with_vcs()?.events);
+
+    for event in vector.events {
+        processor.process(event)?;
+    }
+    assert_eq!(
+        processor.get_management_tel_state(&vector.registry_id)?,
+        vector.registry_state
+    );
+    for (vc_id, state) in vector.vc_states {
+        assert_eq!(processor.get_vc_state(&vc_id)?, state);
+    }
+
+    Ok(())
+}
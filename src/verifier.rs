@@ -0,0 +1,221 @@
+//! Verification of TEL events presented by holder of credential, done purely
+//! in memory, without event database. Events are only checked to carry source
+//! seal, checking it against issuer KEL is left to the caller.
+
+use keri::{event::sections::seal::EventSeal, prefix::Prefix};
+
+use crate::{
+    error::Error,
+    event::{
+        vc_event::{VCEvent, VCEventType},
+        verifiable_event::VerifiableEvent,
+        Event,
+    },
+    state::{
+        vc_state::{fold_vc_events, TelState},
+        ManagerTelState,
+    },
+};
+
+/// Folds management events of registry into its state. Events need to be
+/// ordered by sn and carry source seal.
+pub fn verify_management_tel(events: &[VerifiableEvent]) -> Result<ManagerTelState, Error> {
+    events
+        .iter()
+        .try_fold(ManagerTelState::default(), |state, event| {
+            if !event.is_anchored() {
+                return Err(Error::MissingAnchor);
+            }
+            match event.event {
+                Event::Management(ref man) => state.apply(man),
+                _ => Err(Error::Generic("Improper event type".into())),
+            }
+        })
+}
+
+/// Folds vc events into vc state, checking them against state of their
+/// registry, computed with `verify_management_tel` first. Events are passed as
+/// `VerifiableEvent`s, as they are received from holder, and management event
+/// among them results in error. Events need to be ordered by sn and carry
+/// source seal.
+///
+/// Registry anchor digest can be checked only against the last management
+/// event, which is kept in state. Anchors pointing to earlier management
+/// events are checked with `verify_registry_anchors`.
+pub fn verify_vc_tel(
+    events: &[VerifiableEvent],
+    mgmt: &ManagerTelState,
+) -> Result<TelState, Error> {
+    if mgmt == &ManagerTelState::default() {
+        return Err(Error::WrongState("Registry not incepted".into()));
+    }
+    let vc_events = vc_events(events)?;
+    for event in &vc_events {
+        validate_against_registry(event, mgmt)?;
+    }
+    fold_vc_events(&vc_events)
+}
+
+/// Checks if registry anchors of vc events match management events they
+/// point to. Management events need to be verified with
+/// `verify_management_tel`, so event of given sn is at its index.
+pub fn verify_registry_anchors(
+    events: &[VerifiableEvent],
+    management_events: &[VerifiableEvent],
+) -> Result<(), Error> {
+    vc_events(events)?.iter().try_for_each(|event| {
+        let anchor = match registry_anchor(event) {
+            Some(anchor) => anchor,
+            None => return Ok(()),
+        };
+        let anchored = management_events.get(anchor.sn as usize).ok_or_else(|| {
+            Error::WrongState(format!(
+                "Vc event anchored in unknown management event of sn {}",
+                anchor.sn
+            ))
+        })?;
+        if anchor
+            .event_digest
+            .verify_binding(&anchored.event.chained_bytes()?)
+        {
+            Ok(())
+        } else {
+            Err(Error::Generic("Registry anchor doesn't match".into()))
+        }
+    })
+}
+
+fn vc_events(events: &[VerifiableEvent]) -> Result<Vec<VCEvent>, Error> {
+    events
+        .iter()
+        .map(|event| {
+            if !event.is_anchored() {
+                return Err(Error::MissingAnchor);
+            }
+            match event.event {
+                Event::Vc(ref vc) => Ok(vc.event.clone()),
+                _ => Err(Error::Generic("Improper event type".into())),
+            }
+        })
+        .collect()
+}
+
+fn registry_anchor(event: &VCEvent) -> Option<&EventSeal> {
+    match event.event_type {
+        VCEventType::Bis(ref iss) => Some(&iss.registry_anchor),
+        VCEventType::Brv(ref rev) => rev.registry_anchor.as_ref(),
+        _ => None,
+    }
+}
+
+// Check if vc event belongs to registry and points to its known management
+// event.
+fn validate_against_registry(event: &VCEvent, mgmt: &ManagerTelState) -> Result<(), Error> {
+    if let Some(registry_id) = event.registry_id() {
+        if registry_id != mgmt.prefix {
+            return Err(Error::WrongState(format!(
+                "Vc event from registry {}, expected registry {}",
+                registry_id.to_str(),
+                mgmt.prefix.to_str()
+            )));
+        }
+    }
+    match registry_anchor(event) {
+        Some(anchor) if anchor.sn > mgmt.sn => Err(Error::WrongState(format!(
+            "Vc event anchored in unknown management event of sn {}",
+            anchor.sn
+        ))),
+        Some(anchor) if anchor.sn == mgmt.sn && !anchor.event_digest.verify_binding(&mgmt.last) => {
+            Err(Error::Generic("Registry anchor doesn't match".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[test]
+fn test_verify_in_memory() -> Result<(), Error> {
+    use crate::{event::vc_event::TimestampedVCEvent, seal::EventSourceSeal, tel::event_generator};
+    use keri::{derivation::self_addressing::SelfAddressing, prefix::IdentifierPrefix};
+
+    let seal = EventSourceSeal {
+        sn: 1,
+        digest: "EJJR2nmwyYAfSVPzhzS6b5CMZAoTNZH3ULvaU6Z-i0d8".parse()?,
+    };
+    let backer: IdentifierPrefix = "DSEpNJeSJjxo6oAxkNE8eCOJg2HRPstqkeHWBAvN9XNU".parse()?;
+    let apply = |state: &ManagerTelState, event: &Event| match event {
+        Event::Management(ref man) => state.apply(man),
+        _ => Err(Error::Generic("Improper event type".into())),
+    };
+
+    // Management chain: inception and rotation adding backer.
+    let vcp = event_generator::make_inception_event(
+        "EaKJ0FoLxO1TYmyuprguKO7kJ7Hbn0m0Wuk5aMtSrMtY".parse()?,
+        vec![],
+        0,
+        vec![backer],
+        None,
+        None,
+    )?;
+    let st = apply(&ManagerTelState::default(), &vcp)?;
+    let vrt = event_generator::make_rotation_event(
+        &st,
+        &["BwFbQvUaS4EirvZVPUav7R_KDHB8AKmSfXNpWnZU_YEU".parse::<IdentifierPrefix>()?],
+        &[],
+        None,
+        None,
+    )?;
+    let st = apply(&st, &vrt)?;
+    let management_events = vec![
+        VerifiableEvent::new(vcp, seal.clone().into()),
+        VerifiableEvent::new(vrt, seal.clone().into()),
+    ];
+    let management = verify_management_tel(&management_events)?;
+    assert_eq!(management, st);
+
+    // Vc chain: issuance and revocation.
+    let vc_hash = SelfAddressing::Blake3_256.derive("some vc".as_bytes());
+    let iss = event_generator::make_issuance_event(&st, vc_hash.clone(), None, None)?;
+    let last = match iss {
+        Event::Vc(ref vc) => vc.event.serialize()?,
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    let rev = event_generator::make_revoke_event(&vc_hash, &last, &st, None, None)?;
+    let iss = VerifiableEvent::new(iss, seal.clone().into());
+    let rev = VerifiableEvent::new(rev, seal.clone().into());
+
+    assert_eq!(
+        verify_vc_tel(&[iss.clone()], &management)?,
+        TelState::Issued(last)
+    );
+    assert_eq!(
+        verify_vc_tel(&[iss.clone(), rev.clone()], &management)?,
+        TelState::Revoked
+    );
+    verify_registry_anchors(&[iss.clone(), rev], &management_events)?;
+    // Vc events can't be verified against registry which wasn't incepted.
+    assert!(verify_vc_tel(&[iss.clone()], &ManagerTelState::default()).is_err());
+    assert!(verify_management_tel(&[iss.clone()]).is_err());
+    assert!(verify_registry_anchors(&[iss.clone()], &[]).is_err());
+
+    // Issuance which registry anchor points to management event of known
+    // sn, but its digest doesn't match that event.
+    let forged = match iss.event {
+        Event::Vc(ref vc) => {
+            let mut event = vc.event.clone();
+            match event.event_type {
+                VCEventType::Bis(ref mut bis) => {
+                    bis.registry_anchor.event_digest =
+                        SelfAddressing::Blake3_256.derive("forged".as_bytes())
+                }
+                _ => return Err(Error::Generic("Improper event type".into())),
+            }
+            TimestampedVCEvent::new(event)?
+        }
+        _ => return Err(Error::Generic("Improper event type".into())),
+    };
+    let forged = VerifiableEvent::new(Event::Vc(forged), seal.into());
+    assert!(verify_vc_tel(&[forged.clone()], &management).is_err());
+    assert!(verify_registry_anchors(&[forged], &management_events).is_err());
+
+    Ok(())
+}